@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use tauri::State;
+
+use crate::models::{ContentPack, VersionReport};
+use crate::services::{compare, loader};
+use crate::AppState;
+
+/// Compare two versions of a pack (e.g. two game checkouts, or two releases
+/// of a mod) without adding either to the workspace. The report lists
+/// added, removed and changed entities by type, and references from loaded
+/// editable packs to IDs the new version removed.
+#[tauri::command]
+pub async fn compare_pack_versions(
+    state: State<'_, AppState>,
+    old_path: PathBuf,
+    new_path: PathBuf,
+    exclude_dirs: Option<Vec<String>>,
+    is_base_game: Option<bool>,
+) -> Result<VersionReport, String> {
+    let is_base_game = is_base_game.unwrap_or(false);
+    let old = load_detached(&old_path, exclude_dirs.clone(), is_base_game)?;
+    let new = load_detached(&new_path, exclude_dirs, is_base_game)?;
+
+    let types = compare::compare_packs(&old, &new);
+    let broken_references = {
+        let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+        compare::broken_references(&old, &new, &workspace)
+    };
+
+    Ok(VersionReport {
+        old_name: old.name,
+        new_name: new.name,
+        types,
+        broken_references,
+    })
+}
+
+/// Load a pack read-only without adding it to the workspace
+fn load_detached(
+    path: &Path,
+    exclude_dirs: Option<Vec<String>>,
+    is_base_game: bool,
+) -> Result<ContentPack, String> {
+    let result = loader::load_content_pack(path, true, None, exclude_dirs.clone(), is_base_game)
+        .map_err(|e| e.to_string())?;
+    Ok(loader::create_pack_from_result(
+        &result,
+        path,
+        true,
+        None,
+        exclude_dirs,
+        is_base_game,
+    ))
+}
@@ -0,0 +1,186 @@
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use tauri::State;
+
+use crate::commands::entity::reindex_entity;
+use crate::commands::history::record_journal;
+use crate::models::{EntityKey, PackId, Workspace};
+use crate::services::history::Journal;
+use crate::services::ids::id_namespace;
+use crate::services::inheritance::InheritanceResolver;
+use crate::services::reorganize;
+use crate::AppState;
+
+/// How an entity is brought into another pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CopyMode {
+    /// An independent copy with a new ID and every field spelled out
+    Copy,
+    /// A `copy-from` stub with a new ID that inherits everything
+    CopyFrom,
+    /// A definition with the same ID that replaces the original
+    Override,
+}
+
+/// Result of copying an entity into a pack
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyResult {
+    pub entity_key: EntityKey,
+    /// File the entity was added to, relative to the target pack
+    pub source_file: PathBuf,
+}
+
+/// Create a variant of an entity in an editable pack.
+///
+/// `new_id` is required for `copy` and `copyFrom`. With `prune`, an
+/// `override` is created as a copy-from of the original under the same ID,
+/// so it only holds the fields changed afterwards. The entity goes to
+/// `target_file`, or by default to the source file's path within the target
+/// pack (without the base game's leading `json/`).
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn copy_entity_to_pack(
+    state: State<'_, AppState>,
+    source_pack_id: PackId,
+    entity_key: EntityKey,
+    target_pack_id: PackId,
+    mode: CopyMode,
+    new_id: Option<String>,
+    prune: bool,
+    target_file: Option<PathBuf>,
+) -> Result<CopyResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+
+    let source = workspace
+        .packs
+        .get(&source_pack_id)
+        .ok_or_else(|| format!("Pack {} not found", source_pack_id))?;
+    let target = workspace
+        .packs
+        .get(&target_pack_id)
+        .ok_or_else(|| format!("Pack {} not found", target_pack_id))?;
+    if target.read_only {
+        return Err("Cannot add entities to a read-only pack".to_string());
+    }
+    let entity = source
+        .entities
+        .get(&entity_key)
+        .ok_or_else(|| format!("Entity {} not found", entity_key))?;
+
+    let id = entity.meta.id.clone();
+    let entity_type = entity.meta.entity_type.clone();
+    let namespace = id_namespace(&entity_type);
+    let id_field = ["id", "abstract"]
+        .into_iter()
+        .find(|field| entity.json.get(*field).and_then(|v| v.as_str()) == Some(id.as_str()));
+
+    // Inheriting only works from a definition loaded earlier
+    if mode != CopyMode::Copy {
+        let position = |pack_id| workspace.load_order.iter().position(|p| *p == pack_id);
+        if let (Some(source_position), Some(target_position)) =
+            (position(source_pack_id), position(target_pack_id))
+        {
+            if target_position <= source_position {
+                return Err(format!(
+                    "{} must load after {} to inherit from it",
+                    target.name, source.name
+                ));
+            }
+        }
+    }
+
+    let new_id = match mode {
+        CopyMode::Override => id.clone(),
+        CopyMode::Copy | CopyMode::CopyFrom => {
+            let new_id = new_id
+                .map(|new_id| new_id.trim().to_string())
+                .filter(|new_id| !new_id.is_empty())
+                .ok_or_else(|| "A new ID is required".to_string())?;
+            check_unused(&workspace, namespace, &new_id)?;
+            new_id
+        }
+    };
+
+    let json = match mode {
+        CopyMode::Copy => {
+            let id_field = id_field.ok_or_else(|| no_id_field(&id))?;
+            // Resolved, so the copy doesn't depend on what the original inherits
+            let mut json = InheritanceResolver::from_workspace(&workspace)
+                .resolve(&entity.json, source_pack_id);
+            if let Value::Object(obj) = &mut json {
+                obj.remove("copy-from");
+                obj.insert(id_field.to_string(), Value::String(new_id.clone()));
+            }
+            json
+        }
+        CopyMode::CopyFrom => json!({
+            "type": entity_type,
+            "id": new_id,
+            "copy-from": id,
+        }),
+        CopyMode::Override if prune => {
+            let id_field = id_field.ok_or_else(|| no_id_field(&id))?;
+            json!({
+                "type": entity_type,
+                id_field: id,
+                "copy-from": id,
+            })
+        }
+        CopyMode::Override => entity.json.clone(),
+    };
+
+    let source_file = match &target_file {
+        Some(file) => reorganize::pack_relative_path(target, file),
+        None => reorganize::pack_relative_path(target, &default_target(&entity.source_file)),
+    }
+    .map_err(|e| e.to_string())?;
+    reorganize::check_target(target, &source_file).map_err(|e| e.to_string())?;
+
+    let label = match mode {
+        CopyMode::Copy => format!("Copy {} as {}", id, new_id),
+        CopyMode::CopyFrom => format!("Copy from {} as {}", id, new_id),
+        CopyMode::Override => format!("Override {}", id),
+    };
+    let mut journal = Journal::new(label);
+    let target = workspace.packs.get_mut(&target_pack_id).unwrap();
+    let key = journal.add_entity(target, json, source_file.clone())?;
+    reindex_entity(&state, &workspace, target_pack_id, &key, &key)?;
+    record_journal(&state, &workspace, target_pack_id, journal)?;
+
+    Ok(CopyResult {
+        entity_key: key,
+        source_file,
+    })
+}
+
+/// Where a copy goes by default: the same relative path as the original.
+/// Base game files live under `json/` in its data directory, which mods don't use.
+fn default_target(source_file: &Path) -> PathBuf {
+    let mut components = source_file.components();
+    match components.next() {
+        Some(Component::Normal(first)) if first == "json" => components.collect(),
+        _ => source_file.to_path_buf(),
+    }
+}
+
+fn check_unused(workspace: &Workspace, namespace: &str, id: &str) -> Result<(), String> {
+    for pack in workspace.packs.values() {
+        let defined = pack
+            .entities
+            .values()
+            .any(|e| e.meta.id == id && id_namespace(&e.meta.entity_type) == namespace);
+        if defined {
+            return Err(format!("'{}' is already defined in {}", id, pack.name));
+        }
+    }
+    Ok(())
+}
+
+fn no_id_field(id: &str) -> String {
+    format!(
+        "'{}' can't be copied this way: its ID comes from another field",
+        id
+    )
+}
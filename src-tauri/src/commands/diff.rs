@@ -0,0 +1,166 @@
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::commands::entity::{apply_entity_update, reindex_entity};
+use crate::commands::history::record_journal;
+use crate::models::{
+    DiffBase, EntityDiff, EntityKey, Fix, PackId, PatchOperation, UpdateResult, Workspace,
+};
+use crate::services::diff;
+use crate::services::fixes::{self, pointer_from_path};
+use crate::services::git::{GitError, Repository};
+use crate::services::history::Journal;
+use crate::services::inheritance::InheritanceResolver;
+use crate::AppState;
+
+/// Paths left out when comparing with a base definition
+const IDENTITY_PATHS: &[&str] = &["$.id", "$.abstract", "$.copy-from"];
+
+/// Compare an entity with its saved version (`disk`), its version in the
+/// pack's last git commit (`head`), or with the definition it overrides or
+/// copies from in an earlier pack (`base`).
+/// With `base`, both sides are compared with copy-from resolved.
+#[tauri::command]
+pub fn diff_entity(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+    base: DiffBase,
+) -> Result<EntityDiff, String> {
+    let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let comparison = compare(&workspace, pack_id, &entity_key, base)?;
+
+    let before = comparison.base.clone().unwrap_or_else(|| json!({}));
+    let mut entries = diff::diff(&before, &comparison.current);
+    if base == DiffBase::Base {
+        // A different ID from the parent is the point of copy-from
+        entries.retain(|entry| !IDENTITY_PATHS.contains(&entry.path.as_str()));
+    }
+
+    Ok(EntityDiff {
+        base,
+        base_pack_name: comparison.base_pack_name,
+        has_base: comparison.base.is_some(),
+        entries,
+    })
+}
+
+/// Set one value from a diff back to what it is in the base.
+/// For a copy-from entity compared with its parent, a field that ends up
+/// equal to the parent's is removed so it is inherited again.
+#[tauri::command]
+pub fn revert_entity_field(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+    base: DiffBase,
+    path: String,
+) -> Result<UpdateResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let comparison = compare(&workspace, pack_id, &entity_key, base)?;
+    let base_json = comparison.base.unwrap_or_else(|| json!({}));
+
+    let pointer = pointer_from_path(&path).ok_or_else(|| format!("Invalid path {}", path))?;
+    let top = pointer
+        .split('/')
+        .nth(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .ok_or_else(|| "Cannot revert the whole entity".to_string())?;
+
+    let mut json = workspace.packs[&pack_id].entities[&entity_key].json.clone();
+    let inherits = base == DiffBase::Base && json.get("copy-from").is_some();
+    if inherits && json.get(&top).is_none() {
+        return Err(format!(
+            "'{}' is inherited; edit its relative, proportional, extend or delete entry instead",
+            top
+        ));
+    }
+
+    let operation = match (base_json.pointer(&pointer), json.pointer(&pointer)) {
+        (Some(value), Some(_)) => PatchOperation::Replace {
+            path: pointer.clone(),
+            value: value.clone(),
+        },
+        (Some(value), None) => PatchOperation::Add {
+            path: pointer.clone(),
+            value: value.clone(),
+        },
+        (None, Some(_)) => PatchOperation::Remove {
+            path: pointer.clone(),
+        },
+        (None, None) => return Err(format!("Nothing to revert at {}", path)),
+    };
+    let fix = Fix::new(format!("Revert {}", path), vec![operation]);
+    fixes::apply_fix(&mut json, &fix).map_err(|e| e.to_string())?;
+
+    if inherits && json.get(&top) == base_json.get(&top) {
+        if let Value::Object(obj) = &mut json {
+            obj.remove(&top);
+        }
+    }
+
+    let text = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+    let mut journal = Journal::new(fix.description);
+    let result = apply_entity_update(&mut workspace, &mut journal, pack_id, &entity_key, &text)?;
+    if result.accepted {
+        let new_key = result.new_key.as_deref().unwrap_or(&entity_key);
+        reindex_entity(&state, &workspace, pack_id, &entity_key, new_key)?;
+        record_journal(&state, &workspace, pack_id, journal)?;
+    }
+    Ok(result)
+}
+
+/// The two sides of a diff
+struct Comparison {
+    base: Option<Value>,
+    base_pack_name: Option<String>,
+    current: Value,
+}
+
+fn compare(
+    workspace: &Workspace,
+    pack_id: PackId,
+    entity_key: &EntityKey,
+    base: DiffBase,
+) -> Result<Comparison, String> {
+    let pack = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+    let entity = pack
+        .entities
+        .get(entity_key)
+        .ok_or_else(|| format!("Entity {} not found", entity_key))?;
+
+    match base {
+        DiffBase::Disk => Ok(Comparison {
+            base: diff::disk_version(pack, entity).map_err(|e| e.to_string())?,
+            base_pack_name: None,
+            current: entity.json.clone(),
+        }),
+        DiffBase::Head => {
+            let repository = Repository::open(&pack.path)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| GitError::NotARepository(pack.path.clone()).to_string())?;
+            Ok(Comparison {
+                base: repository
+                    .entity_at("HEAD", entity)
+                    .map_err(|e| e.to_string())?,
+                base_pack_name: None,
+                current: entity.json.clone(),
+            })
+        }
+        DiffBase::Base => {
+            let resolver = InheritanceResolver::from_workspace(workspace);
+            let parent = resolver.base(&entity.json, pack_id);
+            Ok(Comparison {
+                base_pack_name: parent
+                    .as_ref()
+                    .and_then(|(id, _)| workspace.packs.get(id))
+                    .map(|p| p.name.clone()),
+                base: parent.map(|(_, json)| json),
+                current: resolver.resolve(&entity.json, pack_id),
+            })
+        }
+    }
+}
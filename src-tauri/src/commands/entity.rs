@@ -1,9 +1,18 @@
 use tauri::State;
 
-use crate::models::{EntityData, EntityKey, EntityMeta, PackId, UpdateResult};
+use crate::commands::history::record_journal;
+use crate::models::{EntityData, EntityKey, PackId, UpdateResult, Workspace};
+use crate::services::fuzzy::fuzzy_score;
+use crate::services::history::Journal;
+use crate::services::inheritance::InheritanceResolver;
+use crate::services::query::{Candidate, Query};
+use crate::services::search_index::{self, Snippet};
 use crate::services::validator;
 use crate::AppState;
 
+/// Number of recently opened entities remembered for search ranking
+const MAX_RECENT_ENTITIES: usize = 50;
+
 /// Get full entity data for editing
 #[tauri::command]
 pub fn get_entity(
@@ -23,6 +32,14 @@ pub fn get_entity(
         .get(&entity_key)
         .ok_or_else(|| format!("Entity {} not found", entity_key))?;
 
+    // Remember it so search can rank recently opened entities first
+    let mut recent = state.recent_entities.lock().map_err(|e| e.to_string())?;
+    recent.retain(|(id, key)| *id != pack_id || *key != entity_key);
+    if recent.len() == MAX_RECENT_ENTITIES {
+        recent.remove(0);
+    }
+    recent.push((pack_id, entity_key.clone()));
+
     Ok(entity.to_data(pack.read_only))
 }
 
@@ -34,8 +51,64 @@ pub fn update_entity(
     entity_key: EntityKey,
     new_json_text: String,
 ) -> Result<UpdateResult, String> {
-    // First validate the new JSON
-    let validation = validator::validate_json_text(&new_json_text);
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let mut journal = Journal::new(format!("Edit {}", entity_key));
+    let result = apply_entity_update(
+        &mut workspace,
+        &mut journal,
+        pack_id,
+        &entity_key,
+        &new_json_text,
+    )?;
+    if result.accepted {
+        let new_key = result.new_key.as_deref().unwrap_or(&entity_key);
+        reindex_entity(&state, &workspace, pack_id, &entity_key, new_key)?;
+        record_journal(&state, &workspace, pack_id, journal)?;
+    }
+    Ok(result)
+}
+
+/// Bring the search index up to date after an entity's JSON changed.
+/// `old_key` and `new_key` differ when the edit changed its type or ID.
+pub fn reindex_entity(
+    state: &AppState,
+    workspace: &Workspace,
+    pack_id: PackId,
+    old_key: &str,
+    new_key: &str,
+) -> Result<(), String> {
+    let entity = workspace
+        .packs
+        .get(&pack_id)
+        .and_then(|pack| pack.entities.get(new_key))
+        .ok_or_else(|| format!("Entity {} not found", new_key))?;
+
+    state
+        .search_index
+        .lock()
+        .map_err(|e| e.to_string())?
+        .update_entity(pack_id, old_key, new_key, entity);
+    Ok(())
+}
+
+/// Validate new JSON text for an entity and store it if valid.
+/// Shared by every command that edits entity JSON so changes are
+/// validated, dirty-tracked and recorded in `journal` the same way.
+pub fn apply_entity_update(
+    workspace: &mut Workspace,
+    journal: &mut Journal,
+    pack_id: PackId,
+    entity_key: &EntityKey,
+    new_json_text: &str,
+) -> Result<UpdateResult, String> {
+    // First validate the new JSON against everything loaded
+    let validation = {
+        let mut ctx = validator::ValidationContext::from_workspace(workspace);
+        if let Some(pack) = workspace.packs.get(&pack_id) {
+            ctx = ctx.with_pack(pack);
+        }
+        validator::validate_json_text_with_context(new_json_text, &ctx)
+    };
 
     if !validation.valid {
         return Ok(UpdateResult {
@@ -48,15 +121,7 @@ pub fn update_entity(
 
     // Parse the JSON
     let new_json: serde_json::Value =
-        serde_json::from_str(&new_json_text).map_err(|e| e.to_string())?;
-
-    // Extract new metadata
-    let new_meta = EntityMeta::from_json(&new_json)
-        .ok_or_else(|| "Could not extract entity metadata from JSON".to_string())?;
-
-    let new_key = format!("{}:{}", new_meta.entity_type, new_meta.id);
-
-    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+        serde_json::from_str(new_json_text).map_err(|e| e.to_string())?;
 
     let pack = workspace
         .packs
@@ -68,30 +133,9 @@ pub fn update_entity(
         return Err("Cannot modify entities in a read-only pack".to_string());
     }
 
-    // Get the existing entity
-    let entity = pack
-        .entities
-        .get_mut(&entity_key)
-        .ok_or_else(|| format!("Entity {} not found", entity_key))?;
-
-    // Update the entity
-    let source_file = entity.source_file.clone();
-    entity.json = new_json;
-    entity.meta = new_meta.clone();
-    entity.dirty = true;
-
-    // Track dirty file
-    if !pack.dirty_files.contains(&source_file) {
-        pack.dirty_files.push(source_file);
-    }
-
-    // Handle key change
-    let key_changed = new_key != entity_key;
-    if key_changed {
-        // Need to re-insert with new key
-        let entity = pack.entities.remove(&entity_key).unwrap();
-        pack.entities.insert(new_key.clone(), entity);
-    }
+    let new_key = journal.set_entity_json(pack, entity_key, new_json)?;
+    let new_meta = pack.entities[&new_key].meta.clone();
+    let key_changed = new_key != *entity_key;
 
     Ok(UpdateResult {
         validation,
@@ -101,17 +145,32 @@ pub fn update_entity(
     })
 }
 
-/// Search entities across all packs
+/// Default page size for search results
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+/// Relevance added for entities in packs that can be edited
+const EDITABLE_BOOST: f32 = 5.0;
+
+/// Relevance added for the most recently opened entity, fading with age
+const RECENT_BOOST: f32 = 10.0;
+
+/// Search entities across all packs.
+/// The query language is described on [`Query`]; it runs over each entity's
+/// effective JSON (copy-from resolved).
 #[tauri::command]
 pub fn search_entities(
     state: State<'_, AppState>,
     query: String,
     entity_types: Option<Vec<String>>,
     pack_ids: Option<Vec<PackId>>,
-) -> Result<Vec<SearchResult>, String> {
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<SearchPage, String> {
     let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
 
-    let query_lower = query.to_lowercase();
+    let query = Query::parse(&query).map_err(|e| format!("Invalid query: {}", e))?;
+    let recent = state.recent_entities.lock().map_err(|e| e.to_string())?;
+    let resolver = InheritanceResolver::from_workspace(&workspace);
     let mut results = Vec::new();
 
     for (pack_id, pack) in &workspace.packs {
@@ -130,56 +189,160 @@ pub fn search_entities(
                 }
             }
 
-            // Check if entity matches query
-            let matches = entity.meta.id.to_lowercase().contains(&query_lower)
-                || entity
-                    .meta
-                    .display_name
-                    .as_ref()
-                    .map(|n| n.to_lowercase().contains(&query_lower))
-                    .unwrap_or(false);
+            // Only entities that copy from another need resolving
+            let effective = entity
+                .meta
+                .copy_from
+                .as_ref()
+                .map(|_| resolver.resolve(&entity.json, *pack_id));
+            let json = effective.as_ref().unwrap_or(&entity.json);
+            let display_name = effective_display_name(json);
+
+            let candidate = Candidate {
+                id: &entity.meta.id,
+                entity_type: &entity.meta.entity_type,
+                display_name: display_name.as_deref(),
+                json,
+            };
+
+            if let Some(matched_paths) = query.matches(&candidate) {
+                let mut score = if query.text().is_empty() {
+                    0.0
+                } else {
+                    let mut fields = vec![entity.meta.id.as_str()];
+                    fields.extend(display_name.as_deref());
+                    fuzzy_score(query.text(), &fields).unwrap_or(0.0)
+                };
+                if !pack.read_only {
+                    score += EDITABLE_BOOST;
+                }
+                if let Some(age) = recent
+                    .iter()
+                    .rev()
+                    .position(|(id, key)| id == pack_id && key == entity_key)
+                {
+                    score += RECENT_BOOST * (1.0 - age as f32 / MAX_RECENT_ENTITIES as f32);
+                }
 
-            if matches {
                 results.push(SearchResult {
                     pack_id: *pack_id,
                     pack_name: pack.name.clone(),
                     entity_key: entity_key.clone(),
                     entity_id: entity.meta.id.clone(),
                     entity_type: entity.meta.entity_type.clone(),
-                    display_name: entity.meta.display_name.clone(),
+                    display_name,
+                    matched_paths,
+                    score,
                 });
             }
         }
     }
 
-    // Sort by relevance (exact matches first, then alphabetically)
+    // Sort by relevance, then alphabetically
     results.sort_by(|a, b| {
-        let a_exact = a.entity_id.to_lowercase() == query_lower
-            || a.display_name
-                .as_ref()
-                .map(|n| n.to_lowercase() == query_lower)
-                .unwrap_or(false);
-        let b_exact = b.entity_id.to_lowercase() == query_lower
-            || b.display_name
-                .as_ref()
-                .map(|n| n.to_lowercase() == query_lower)
-                .unwrap_or(false);
-
-        match (a_exact, b_exact) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => {
-                let a_name = a.display_name.as_ref().unwrap_or(&a.entity_id);
-                let b_name = b.display_name.as_ref().unwrap_or(&b.entity_id);
-                a_name.cmp(b_name)
-            }
-        }
+        let a_name = a.display_name.as_ref().unwrap_or(&a.entity_id);
+        let b_name = b.display_name.as_ref().unwrap_or(&b.entity_id);
+        // Break ties so pages are stable between requests
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a_name.cmp(b_name))
+            .then_with(|| a.entity_key.cmp(&b.entity_key))
+            .then_with(|| a.pack_name.cmp(&b.pack_name))
     });
 
-    // Limit results
-    results.truncate(100);
+    let total = results.len();
+    let offset = offset.unwrap_or(0);
+    let results = results
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .collect();
+
+    Ok(SearchPage {
+        results,
+        total,
+        offset,
+    })
+}
+
+/// Search the text of every string value in loaded entities
+/// (descriptions, messages, nested fields), using the full-text index.
+#[tauri::command]
+pub fn full_text_search(
+    state: State<'_, AppState>,
+    query: String,
+    entity_types: Option<Vec<String>>,
+    pack_ids: Option<Vec<PackId>>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<FullTextPage, String> {
+    let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let index = state.search_index.lock().map_err(|e| e.to_string())?;
+
+    let entity = |pack_id: PackId, key: &str| {
+        workspace
+            .packs
+            .get(&pack_id)
+            .and_then(|pack| pack.entities.get(key))
+    };
+
+    let hits = index.search(
+        &query,
+        |pack_id, key| entity(pack_id, key).map(|e| &e.json),
+        |pack_id, key| {
+            let pack_ok = pack_ids.as_ref().is_none_or(|ids| ids.contains(&pack_id));
+            let type_ok = entity_types.as_ref().is_none_or(|types| {
+                entity(pack_id, key).is_some_and(|e| types.contains(&e.meta.entity_type))
+            });
+            pack_ok && type_ok
+        },
+    );
+
+    let total = hits.len();
+    let offset = offset.unwrap_or(0);
+    let results = hits
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .filter_map(|hit| {
+            let pack = workspace.packs.get(&hit.pack_id)?;
+            let entity = pack.entities.get(&hit.entity_key)?;
+            Some(FullTextResult {
+                pack_id: hit.pack_id,
+                pack_name: pack.name.clone(),
+                entity_id: entity.meta.id.clone(),
+                entity_type: entity.meta.entity_type.clone(),
+                display_name: entity.meta.display_name.clone(),
+                score: hit.score,
+                snippets: search_index::snippets(&entity.json, &query),
+                entity_key: hit.entity_key,
+            })
+        })
+        .collect();
+
+    Ok(FullTextPage {
+        results,
+        total,
+        offset,
+    })
+}
 
-    Ok(results)
+/// Display name from effective JSON (a string, or an object with "str")
+fn effective_display_name(json: &serde_json::Value) -> Option<String> {
+    let name = json.get("name")?;
+    name.as_str()
+        .or_else(|| name.get("str").and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
+
+/// One page of search results
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Number of matches across all pages
+    pub total: usize,
+    pub offset: usize,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -191,4 +354,33 @@ pub struct SearchResult {
     pub entity_id: String,
     pub entity_type: String,
     pub display_name: Option<String>,
+    /// JSON paths (in the effective JSON) that matched the query
+    pub matched_paths: Vec<String>,
+    /// Relevance; higher is better
+    pub score: f32,
+}
+
+/// One page of full-text search results
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullTextPage {
+    pub results: Vec<FullTextResult>,
+    /// Number of matches across all pages
+    pub total: usize,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullTextResult {
+    pub pack_id: PackId,
+    pub pack_name: String,
+    pub entity_key: EntityKey,
+    pub entity_id: String,
+    pub entity_type: String,
+    pub display_name: Option<String>,
+    /// Relevance; higher is better
+    pub score: f32,
+    /// String values containing the query words, with the words highlighted
+    pub snippets: Vec<Snippet>,
 }
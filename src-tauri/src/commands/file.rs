@@ -1,9 +1,11 @@
-use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use tauri::State;
 
-use crate::models::PackId;
+use crate::commands::history::record_journal;
+use crate::models::{ContentPack, EntityKey, EntityMeta, EntityTree, PackId, Workspace};
+use crate::services::history::Journal;
+use crate::services::reorganize;
 use crate::AppState;
 
 /// Fields that should appear first in JSON objects, in this order
@@ -105,10 +107,13 @@ fn write_indent(out: &mut Vec<u8>, level: usize) {
 #[serde(rename_all = "camelCase")]
 pub struct SaveResult {
     pub files_written: Vec<String>,
+    /// Files removed because every entity was moved out of them
+    pub files_deleted: Vec<String>,
     pub entities_saved: usize,
 }
 
-/// Save all dirty entities in a pack back to their source files
+/// Save all dirty files in a pack: entities are written to their current
+/// source files, new files are created and emptied files are deleted
 #[tauri::command]
 pub async fn save_pack(state: State<'_, AppState>, pack_id: PackId) -> Result<SaveResult, String> {
     let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
@@ -125,59 +130,100 @@ pub async fn save_pack(state: State<'_, AppState>, pack_id: PackId) -> Result<Sa
     if pack.dirty_files.is_empty() {
         return Ok(SaveResult {
             files_written: Vec::new(),
+            files_deleted: Vec::new(),
             entities_saved: 0,
         });
     }
 
-    // Group entities by source file
-    let mut entities_by_file: HashMap<PathBuf, Vec<(usize, serde_json::Value)>> = HashMap::new();
-
-    for entity in pack.entities.values() {
-        if entity.dirty {
-            entities_by_file
-                .entry(entity.source_file.clone())
-                .or_default()
-                .push((entity.array_index, entity.json.clone()));
-        }
-    }
-
     let mut files_written = Vec::new();
+    let mut files_deleted = Vec::new();
     let mut entities_saved = 0;
+    let mut new_indices: Vec<(EntityKey, usize)> = Vec::new();
 
-    // For each dirty file, read it, update the entities, and write it back
-    for (relative_path, dirty_entities) in &entities_by_file {
-        let full_path = pack.path.join(relative_path);
+    // Every dirty file is read before any is written, so a file with
+    // entities the pack didn't load (added on disk since) stops the save
+    // before anything is lost
+    let mut on_disk = Vec::new();
+    for relative_path in pack.dirty_files.clone() {
+        let full_path = pack.path.join(&relative_path);
 
-        // Read the existing file
-        let content = fs::read_to_string(&full_path).map_err(|e| {
-            format!(
-                "Failed to read {}: {}",
-                relative_path.display(),
-                e
-            )
-        })?;
-
-        let mut json_array: Vec<serde_json::Value> =
-            serde_json::from_str(&content).map_err(|e| {
+        // Entries that aren't entities (comments and the like) stay where they were
+        let mut kept: Vec<(usize, serde_json::Value)> = Vec::new();
+        if full_path.exists() {
+            let content = fs::read_to_string(&full_path).map_err(|e| {
                 format!(
-                    "Failed to parse {}: {}",
+                    "Failed to read {}: {}",
                     relative_path.display(),
                     e
                 )
             })?;
 
-        // Update the entities in the array
-        for (array_index, new_json) in dirty_entities {
-            if *array_index < json_array.len() {
-                json_array[*array_index] = new_json.clone();
-                entities_saved += 1;
+            let json_array: Vec<serde_json::Value> = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {}: {}", relative_path.display(), e))?;
+
+            let loaded = pack.disk_keys.get(&relative_path);
+            for (index, value) in json_array.into_iter().enumerate() {
+                let Some(meta) = EntityMeta::from_json(&value) else {
+                    kept.push((index, value));
+                    continue;
+                };
+                let key = format!("{}:{}", meta.entity_type, meta.id);
+                if !loaded.is_some_and(|keys| keys.contains(&key)) {
+                    return Err(format!(
+                        "{} has {}, which wasn't loaded with the pack; reload the pack before saving",
+                        relative_path.display(),
+                        key
+                    ));
+                }
+            }
+        }
+        on_disk.push((relative_path, full_path, kept));
+    }
+
+    // Each dirty file is rebuilt from the entities that belong to it now,
+    // so entities moved in or out since loading land in the right place
+    for (relative_path, full_path, kept) in on_disk {
+        let mut entries: Vec<(usize, Option<&EntityKey>, serde_json::Value)> = kept
+            .into_iter()
+            .map(|(index, value)| (index, None, value))
+            .collect();
+
+        for (key, entity) in &pack.entities {
+            if entity.source_file == relative_path {
+                entries.push((entity.array_index, Some(key), entity.json.clone()));
+                if entity.dirty {
+                    entities_saved += 1;
+                }
+            }
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        // Everything was moved out of it
+        if entries.is_empty() {
+            if full_path.exists() {
+                fs::remove_file(&full_path)
+                    .map_err(|e| format!("Failed to delete {}: {}", relative_path.display(), e))?;
+                files_deleted.push(relative_path.to_string_lossy().to_string());
+            }
+            pack.disk_keys.remove(&relative_path);
+            continue;
+        }
+
+        for (position, (_, key, _)) in entries.iter().enumerate() {
+            if let Some(key) = key {
+                new_indices.push(((*key).clone(), position));
             }
         }
 
         // Write back to file with pretty formatting, priority fields first
-        let array_value = serde_json::Value::Array(json_array);
+        let array_value =
+            serde_json::Value::Array(entries.into_iter().map(|(_, _, value)| value).collect());
         let output = serialize_with_priority_fields(&array_value);
 
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
         fs::write(&full_path, output).map_err(|e| {
             format!(
                 "Failed to write {}: {}",
@@ -187,6 +233,20 @@ pub async fn save_pack(state: State<'_, AppState>, pack_id: PackId) -> Result<Sa
         })?;
 
         files_written.push(relative_path.to_string_lossy().to_string());
+        let written = pack
+            .entities
+            .values()
+            .filter(|entity| entity.source_file == relative_path)
+            .map(|entity| entity.key())
+            .collect();
+        pack.disk_keys.insert(relative_path, written);
+    }
+
+    // Positions now match the files as written
+    for (key, array_index) in new_indices {
+        if let Some(entity) = pack.entities.get_mut(&key) {
+            entity.array_index = array_index;
+        }
     }
 
     // Clear dirty flags
@@ -197,8 +257,123 @@ pub async fn save_pack(state: State<'_, AppState>, pack_id: PackId) -> Result<Sa
     }
     pack.dirty_files.clear();
 
+    if let Some(history) = state
+        .history
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_mut(&pack_id)
+    {
+        history.mark_saved(pack);
+    }
+
     Ok(SaveResult {
         files_written,
+        files_deleted,
         entities_saved,
     })
 }
+
+/// Result of moving entities between files
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorganizeResult {
+    /// Keys of the entities that moved
+    pub moved: Vec<EntityKey>,
+    /// The pack's entity tree afterwards
+    pub entity_tree: EntityTree,
+}
+
+/// Move entities to the end of another file in the same pack, which is
+/// created on save if it doesn't exist. Entities keep their relative order.
+#[tauri::command]
+pub fn move_entities(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_keys: Vec<EntityKey>,
+    target_file: PathBuf,
+) -> Result<ReorganizeResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let pack = editable_pack(&mut workspace, pack_id)?;
+    let target = reorganize::pack_relative_path(pack, &target_file).map_err(|e| e.to_string())?;
+
+    let mut journal = Journal::new(format!(
+        "Move {} entities to {}",
+        entity_keys.len(),
+        target.display()
+    ));
+    let moved = reorganize::move_entities(&mut journal, pack, &entity_keys, &target)
+        .map_err(|e| e.to_string())?;
+    finish(&state, &workspace, pack_id, journal, moved)
+}
+
+/// Move each type of entity in a file to its own `{stem}_{type}.json` file
+/// next to it. The original file is deleted on save once it's empty.
+#[tauri::command]
+pub fn split_file_by_type(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    file: PathBuf,
+) -> Result<ReorganizeResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let pack = editable_pack(&mut workspace, pack_id)?;
+    let file = reorganize::pack_relative_path(pack, &file).map_err(|e| e.to_string())?;
+
+    let mut journal = Journal::new(format!("Split {} by type", file.display()));
+    let moved = reorganize::file_entities(pack, &file);
+    reorganize::split_by_type(&mut journal, pack, &file).map_err(|e| e.to_string())?;
+    finish(&state, &workspace, pack_id, journal, moved)
+}
+
+/// Move every entity in `files` into `target_file`, one file after another.
+/// Files left empty are deleted on save.
+#[tauri::command]
+pub fn merge_files(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    files: Vec<PathBuf>,
+    target_file: PathBuf,
+) -> Result<ReorganizeResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let pack = editable_pack(&mut workspace, pack_id)?;
+    let target = reorganize::pack_relative_path(pack, &target_file).map_err(|e| e.to_string())?;
+    let files = files
+        .iter()
+        .map(|file| reorganize::pack_relative_path(pack, file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut journal = Journal::new(format!(
+        "Merge {} files into {}",
+        files.len(),
+        target.display()
+    ));
+    let moved =
+        reorganize::merge_files(&mut journal, pack, &files, &target).map_err(|e| e.to_string())?;
+    finish(&state, &workspace, pack_id, journal, moved)
+}
+
+fn editable_pack(workspace: &mut Workspace, pack_id: PackId) -> Result<&mut ContentPack, String> {
+    let pack = workspace
+        .packs
+        .get_mut(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+    if pack.read_only {
+        return Err("Cannot modify a read-only pack".to_string());
+    }
+    Ok(pack)
+}
+
+/// Record a reorganization as one undoable change
+fn finish(
+    state: &AppState,
+    workspace: &Workspace,
+    pack_id: PackId,
+    journal: Journal,
+    moved: Vec<EntityKey>,
+) -> Result<ReorganizeResult, String> {
+    record_journal(state, workspace, pack_id, journal)?;
+    Ok(ReorganizeResult {
+        moved,
+        entity_tree: workspace.packs[&pack_id].to_entity_tree(),
+    })
+}
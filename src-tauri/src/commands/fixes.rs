@@ -0,0 +1,128 @@
+use tauri::State;
+
+use crate::commands::entity::{apply_entity_update, reindex_entity};
+use crate::commands::history::record_journal;
+use crate::models::{EntityKey, Fix, PackId, UpdateResult};
+use crate::services::history::Journal;
+use crate::services::{fixes, validator};
+use crate::AppState;
+
+/// Upper bound on fixes applied to one entity, in case a fix doesn't
+/// clear the finding it was attached to
+const MAX_FIXES_PER_ENTITY: usize = 64;
+
+/// Result of applying every fix for a validation code across a pack
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyFixesResult {
+    /// Keys of entities that were changed (after any key change)
+    pub entities_changed: Vec<EntityKey>,
+    /// Total number of fixes applied
+    pub fixes_applied: usize,
+    /// Entities whose fixed JSON failed validation and was not stored
+    pub rejected: Vec<EntityKey>,
+}
+
+/// Apply a single fix from a validation result to an entity
+#[tauri::command]
+pub fn apply_fix(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+    fix: Fix,
+) -> Result<UpdateResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+
+    let mut json = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?
+        .entities
+        .get(&entity_key)
+        .ok_or_else(|| format!("Entity {} not found", entity_key))?
+        .json
+        .clone();
+
+    fixes::apply_fix(&mut json, &fix).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+
+    let mut journal = Journal::new(format!("Apply fix: {}", fix.description));
+    let result = apply_entity_update(&mut workspace, &mut journal, pack_id, &entity_key, &text)?;
+    if result.accepted {
+        let new_key = result.new_key.as_deref().unwrap_or(&entity_key);
+        reindex_entity(&state, &workspace, pack_id, &entity_key, new_key)?;
+        record_journal(&state, &workspace, pack_id, journal)?;
+    }
+    Ok(result)
+}
+
+/// Apply every available fix for a validation code to all entities in a pack
+#[tauri::command]
+pub fn apply_fixes_by_code(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    code: String,
+) -> Result<ApplyFixesResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+
+    let pack = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+
+    if pack.read_only {
+        return Err("Cannot modify entities in a read-only pack".to_string());
+    }
+
+    let mut updates = Vec::new();
+    {
+        let ctx = validator::ValidationContext::from_workspace(&workspace).with_pack(pack);
+        let mut keys: Vec<&EntityKey> = pack.entities.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let mut json = pack.entities[key].json.clone();
+            let mut applied = 0;
+
+            // Fixes can shift array indices, so re-validate after each one
+            while applied < MAX_FIXES_PER_ENTITY {
+                let validation = validator::validate_entity_json_with_context(&json, &ctx);
+                let Some(fix) = validation.fixes_for_code(&code).next() else {
+                    break;
+                };
+                let before = json.clone();
+                if fixes::apply_fix(&mut json, fix).is_err() || json == before {
+                    break;
+                }
+                applied += 1;
+            }
+
+            if applied > 0 {
+                let text = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+                updates.push((key.clone(), text, applied));
+            }
+        }
+    }
+
+    let mut result = ApplyFixesResult {
+        entities_changed: Vec::new(),
+        fixes_applied: 0,
+        rejected: Vec::new(),
+    };
+
+    let mut journal = Journal::new(format!("Apply {} fixes", code));
+    for (key, text, applied) in updates {
+        let update = apply_entity_update(&mut workspace, &mut journal, pack_id, &key, &text)?;
+        if update.accepted {
+            let new_key = update.new_key.unwrap_or_else(|| key.clone());
+            reindex_entity(&state, &workspace, pack_id, &key, &new_key)?;
+            result.entities_changed.push(new_key);
+            result.fixes_applied += applied;
+        } else {
+            result.rejected.push(key);
+        }
+    }
+    record_journal(&state, &workspace, pack_id, journal)?;
+
+    Ok(result)
+}
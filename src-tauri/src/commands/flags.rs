@@ -0,0 +1,32 @@
+use tauri::State;
+
+use crate::services::flags::{FlagCategory, FlagInfo};
+use crate::services::validator::ValidationContext;
+use crate::AppState;
+
+/// List flags known for an entity type, for autocompletion.
+/// Without an entity type, flags for every category are returned.
+#[tauri::command]
+pub fn list_known_flags(
+    state: State<'_, AppState>,
+    entity_type: Option<String>,
+) -> Result<Vec<FlagInfo>, String> {
+    let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let ctx = ValidationContext::from_workspace(&workspace);
+
+    let categories = match entity_type {
+        Some(entity_type) => vec![FlagCategory::for_type(&entity_type)
+            .ok_or_else(|| format!("Entity type {} has no flags", entity_type))?],
+        None => vec![
+            FlagCategory::Item,
+            FlagCategory::Terrain,
+            FlagCategory::Monster,
+            FlagCategory::VehiclePart,
+        ],
+    };
+
+    Ok(categories
+        .into_iter()
+        .flat_map(|category| ctx.flags().flags_for(category))
+        .collect())
+}
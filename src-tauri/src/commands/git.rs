@@ -0,0 +1,172 @@
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::entity::{apply_entity_update, reindex_entity};
+use crate::commands::history::record_journal;
+use crate::models::{
+    Entity, EntityBlame, EntityKey, FileStatus, PackGitStatus, PackId, UpdateResult, Workspace,
+};
+use crate::services::diff;
+use crate::services::git::{GitError, Repository};
+use crate::services::history::Journal;
+use crate::AppState;
+
+/// Staged, modified and untracked files in a pack, merged with the files
+/// that have unsaved edits. Works for packs outside a repository, and
+/// without git installed, too.
+#[tauri::command]
+pub async fn get_pack_git_status(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+) -> Result<PackGitStatus, String> {
+    let (pack_path, dirty_files) = {
+        let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+        let pack = workspace
+            .packs
+            .get(&pack_id)
+            .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+        (pack.path.clone(), pack.dirty_files.clone())
+    };
+
+    // Without git the pack is treated as outside a repository, so the
+    // unsaved files still show
+    let repository = match Repository::open(&pack_path) {
+        Ok(repository) => repository,
+        Err(GitError::NotInstalled) => None,
+        Err(e) => return Err(e.to_string()),
+    };
+    let (branch, mut files) = match &repository {
+        Some(repository) => (
+            repository.branch().map_err(|e| e.to_string())?,
+            repository.status().map_err(|e| e.to_string())?,
+        ),
+        None => (None, Vec::new()),
+    };
+
+    for dirty_file in dirty_files {
+        match files.iter_mut().find(|file| file.path == dirty_file) {
+            Some(file) => file.unsaved = true,
+            None => files.push(FileStatus {
+                path: dirty_file,
+                staged: false,
+                modified: false,
+                untracked: false,
+                unsaved: true,
+            }),
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(PackGitStatus {
+        is_repository: repository.is_some(),
+        branch,
+        files,
+    })
+}
+
+/// The entity as it is in the pack's last commit, as JSON text.
+/// Returns None if it isn't in the last commit.
+#[tauri::command]
+pub async fn get_committed_entity(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+) -> Result<Option<String>, String> {
+    let (pack_path, entity) = {
+        let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+        let (pack_path, entity) = locate(&workspace, pack_id, &entity_key)?;
+        (pack_path.to_path_buf(), entity.clone())
+    };
+    let repository = open_repository(&pack_path)?;
+
+    let committed = repository
+        .entity_at("HEAD", &entity)
+        .map_err(|e| e.to_string())?;
+    committed
+        .map(|json| serde_json::to_string_pretty(&json).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Which commit last changed an entity, and whether it has changed since
+#[tauri::command]
+pub async fn blame_entity(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+) -> Result<EntityBlame, String> {
+    // Searching history runs many git commands, so it's done without
+    // holding the workspace
+    let (pack_path, entity, on_disk) = {
+        let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+        let (pack_path, entity) = locate(&workspace, pack_id, &entity_key)?;
+        let on_disk =
+            diff::disk_version(&workspace.packs[&pack_id], entity).map_err(|e| e.to_string())?;
+        (pack_path.to_path_buf(), entity.clone(), on_disk)
+    };
+    let repository = open_repository(&pack_path)?;
+
+    let committed = repository
+        .entity_at("HEAD", &entity)
+        .map_err(|e| e.to_string())?;
+    let last_commit = repository.last_change(&entity).map_err(|e| e.to_string())?;
+
+    Ok(EntityBlame {
+        last_commit,
+        uncommitted: entity.dirty || on_disk != committed,
+    })
+}
+
+/// Replace an entity with its version in the pack's last commit.
+/// The result is an ordinary edit: it can be undone and still has to be saved.
+#[tauri::command]
+pub async fn revert_entity_to_head(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+) -> Result<UpdateResult, String> {
+    let (pack_path, entity) = {
+        let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+        let (pack_path, entity) = locate(&workspace, pack_id, &entity_key)?;
+        (pack_path.to_path_buf(), entity.clone())
+    };
+    let repository = open_repository(&pack_path)?;
+    let committed = repository
+        .entity_at("HEAD", &entity)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("{} is not in the last commit", entity_key))?;
+
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(&committed).map_err(|e| e.to_string())?;
+    let mut journal = Journal::new(format!("Revert {} to last commit", entity_key));
+    let result = apply_entity_update(&mut workspace, &mut journal, pack_id, &entity_key, &text)?;
+    if result.accepted {
+        let new_key = result.new_key.as_deref().unwrap_or(&entity_key);
+        reindex_entity(&state, &workspace, pack_id, &entity_key, new_key)?;
+        record_journal(&state, &workspace, pack_id, journal)?;
+    }
+    Ok(result)
+}
+
+/// A pack's directory and one of its entities
+fn locate<'w>(
+    workspace: &'w Workspace,
+    pack_id: PackId,
+    entity_key: &EntityKey,
+) -> Result<(&'w Path, &'w Entity), String> {
+    let pack = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+    let entity = pack
+        .entities
+        .get(entity_key)
+        .ok_or_else(|| format!("Entity {} not found", entity_key))?;
+    Ok((&pack.path, entity))
+}
+
+/// The repository a pack directory is in
+fn open_repository(pack_path: &Path) -> Result<Repository, String> {
+    Repository::open(pack_path)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| GitError::NotARepository(pack_path.to_path_buf()).to_string())
+}
@@ -0,0 +1,82 @@
+use tauri::State;
+
+use crate::models::{PackId, Workspace};
+use crate::services::history::{HistoryState, HistoryStep, Journal, PackHistory};
+use crate::AppState;
+
+/// Undo the pack's most recent change. Returns None if there is nothing to undo.
+#[tauri::command]
+pub fn undo(state: State<'_, AppState>, pack_id: PackId) -> Result<Option<HistoryStep>, String> {
+    step(&state, pack_id, PackHistory::undo)
+}
+
+/// Redo the pack's most recently undone change. Returns None if there is nothing to redo.
+#[tauri::command]
+pub fn redo(state: State<'_, AppState>, pack_id: PackId) -> Result<Option<HistoryStep>, String> {
+    step(&state, pack_id, PackHistory::redo)
+}
+
+/// What undo and redo would do next for a pack
+#[tauri::command]
+pub fn get_history_state(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+) -> Result<HistoryState, String> {
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    Ok(history
+        .get(&pack_id)
+        .map(PackHistory::state)
+        .unwrap_or(HistoryState {
+            undo_label: None,
+            redo_label: None,
+        }))
+}
+
+fn step(
+    state: &AppState,
+    pack_id: PackId,
+    apply: fn(&mut PackHistory, &mut crate::models::ContentPack) -> Option<HistoryStep>,
+) -> Result<Option<HistoryStep>, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let pack = workspace
+        .packs
+        .get_mut(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+
+    let mut history = state.history.lock().map_err(|e| e.to_string())?;
+    let Some(step) = history.get_mut(&pack_id).and_then(|h| apply(h, pack)) else {
+        return Ok(None);
+    };
+
+    let mut index = state.search_index.lock().map_err(|e| e.to_string())?;
+    for key in &step.changed_keys {
+        match pack.entities.get(key) {
+            Some(entity) => index.update_entity(pack_id, key, key, entity),
+            None => index.remove_entity(pack_id, key),
+        }
+    }
+
+    Ok(Some(step))
+}
+
+/// Add a finished command's changes to the pack's history
+pub fn record_journal(
+    state: &AppState,
+    workspace: &Workspace,
+    pack_id: PackId,
+    journal: Journal,
+) -> Result<(), String> {
+    let Some(pack) = workspace.packs.get(&pack_id) else {
+        return Ok(());
+    };
+    if let Some(transaction) = journal.finish(pack) {
+        state
+            .history
+            .lock()
+            .map_err(|e| e.to_string())?
+            .entry(pack_id)
+            .or_default()
+            .record(transaction);
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use tauri::{AppHandle, State};
+
+use crate::commands::settings::get_settings;
+use crate::models::PackId;
+use crate::services::ids::{self, IdIssue};
+use crate::AppState;
+
+/// Check a pack's IDs for collisions with other packs, accidental overrides
+/// and the prefix convention. Without `prefix_pattern`, the pattern from
+/// settings is used, or "{mod_id}_" if none is set.
+#[tauri::command]
+pub fn check_pack_ids(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    prefix_pattern: Option<String>,
+) -> Result<Vec<IdIssue>, String> {
+    let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+
+    let pack = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+
+    let prefix_pattern = match prefix_pattern.filter(|p| !p.is_empty()) {
+        Some(pattern) => pattern,
+        None => get_settings(app)?
+            .id_prefix_pattern
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| ids::DEFAULT_PREFIX_PATTERN.to_string()),
+    };
+
+    Ok(ids::check_pack_ids(&workspace, pack, &prefix_pattern))
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::State;
+
+use crate::commands::entity::{apply_entity_update, reindex_entity};
+use crate::commands::history::record_journal;
+use crate::models::{
+    EntityKey, Mapgen, MapgenKind, MapgenObject, PackId, PaletteRef, Placement, SymbolMap,
+    UpdateResult,
+};
+use crate::services::history::Journal;
+use crate::AppState;
+
+/// A mapgen entity as the map editor sees it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapgenData {
+    pub kind: MapgenKind,
+    /// Overmap terrain IDs, row by row for multi-tile maps
+    pub om_terrain: Vec<String>,
+    /// Size in tiles, when it can be told
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub weight: Option<i64>,
+    pub fill_ter: Option<String>,
+    pub rows: Vec<String>,
+    pub palettes: Vec<PaletteRef>,
+    pub terrain: SymbolMap,
+    pub furniture: SymbolMap,
+    pub items: SymbolMap,
+    pub monsters: SymbolMap,
+    pub nested: SymbolMap,
+    /// `place_*` sections by name
+    pub placements: BTreeMap<String, Vec<Placement>>,
+}
+
+/// Changes to a mapgen; fields left out stay as they are
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapgenEdit {
+    pub rows: Option<Vec<String>>,
+    /// An empty string removes `fill_ter`
+    pub fill_ter: Option<String>,
+    pub weight: Option<i64>,
+    pub palettes: Option<Vec<PaletteRef>>,
+    pub terrain: Option<SymbolMap>,
+    pub furniture: Option<SymbolMap>,
+    pub items: Option<SymbolMap>,
+    pub monsters: Option<SymbolMap>,
+    pub nested: Option<SymbolMap>,
+    /// `place_*` sections to replace by name; an empty list removes one
+    pub placements: Option<BTreeMap<String, Vec<Placement>>>,
+}
+
+/// Read a mapgen entity through the typed mapgen model
+#[tauri::command]
+pub fn get_mapgen(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+) -> Result<MapgenData, String> {
+    let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let entity = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?
+        .entities
+        .get(&entity_key)
+        .ok_or_else(|| format!("Entity {} not found", entity_key))?;
+
+    let mapgen = Mapgen::from_json(&entity.json)?;
+    let size = mapgen.size();
+    let object = mapgen.object.clone().unwrap_or_default();
+
+    Ok(MapgenData {
+        kind: mapgen.kind(),
+        om_terrain: mapgen
+            .om_terrain
+            .as_ref()
+            .map(|om| om.ids().into_iter().map(String::from).collect())
+            .unwrap_or_default(),
+        width: size.map(|(width, _)| width),
+        height: size.map(|(_, height)| height),
+        weight: mapgen.weight,
+        fill_ter: object.fill_ter,
+        rows: object.rows.unwrap_or_default(),
+        palettes: object.palettes.unwrap_or_default(),
+        terrain: object.terrain.unwrap_or_default(),
+        furniture: object.furniture.unwrap_or_default(),
+        items: object.items.unwrap_or_default(),
+        monsters: object.monsters.unwrap_or_default(),
+        nested: object.nested.unwrap_or_default(),
+        placements: object.placements,
+    })
+}
+
+/// Change a mapgen through the typed model. Everything the edit doesn't
+/// touch is written back exactly as it was.
+#[tauri::command]
+pub fn update_mapgen(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+    edit: MapgenEdit,
+) -> Result<UpdateResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let entity = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?
+        .entities
+        .get(&entity_key)
+        .ok_or_else(|| format!("Entity {} not found", entity_key))?;
+
+    let mut mapgen = Mapgen::from_json(&entity.json)?;
+    let label = format!("Edit mapgen {}", entity.meta.id);
+    if let Some(weight) = edit.weight {
+        mapgen.weight = Some(weight);
+    }
+    // A mapgen without an `object` (e.g. one that only has a weight) keeps
+    // not having one unless the edit changes something in it
+    let touches_object = edit.rows.is_some()
+        || edit.fill_ter.is_some()
+        || edit.palettes.is_some()
+        || edit.terrain.is_some()
+        || edit.furniture.is_some()
+        || edit.items.is_some()
+        || edit.monsters.is_some()
+        || edit.nested.is_some()
+        || edit.placements.is_some();
+    if touches_object {
+        let object = mapgen.object.get_or_insert_with(MapgenObject::default);
+        if let Some(rows) = edit.rows {
+            object.rows = Some(rows);
+        }
+        if let Some(fill_ter) = edit.fill_ter {
+            object.fill_ter = Some(fill_ter).filter(|f| !f.is_empty());
+        }
+        if let Some(palettes) = edit.palettes {
+            object.palettes = Some(palettes).filter(|p| !p.is_empty());
+        }
+        if let Some(terrain) = edit.terrain {
+            object.terrain = Some(terrain).filter(|t| !t.is_empty());
+        }
+        if let Some(furniture) = edit.furniture {
+            object.furniture = Some(furniture).filter(|f| !f.is_empty());
+        }
+        if let Some(items) = edit.items {
+            object.items = Some(items).filter(|i| !i.is_empty());
+        }
+        if let Some(monsters) = edit.monsters {
+            object.monsters = Some(monsters).filter(|m| !m.is_empty());
+        }
+        if let Some(nested) = edit.nested {
+            object.nested = Some(nested).filter(|n| !n.is_empty());
+        }
+        for (name, placements) in edit.placements.into_iter().flatten() {
+            if !name.starts_with("place_") {
+                return Err(format!("{} is not a place_* section", name));
+            }
+            if placements.is_empty() {
+                object.placements.remove(&name);
+            } else {
+                object.placements.insert(name, placements);
+            }
+        }
+    }
+
+    let json = mapgen.to_json()?;
+    let text = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+    let mut journal = Journal::new(label);
+    let result = apply_entity_update(&mut workspace, &mut journal, pack_id, &entity_key, &text)?;
+    if result.accepted {
+        let new_key = result.new_key.as_deref().unwrap_or(&entity_key);
+        reindex_entity(&state, &workspace, pack_id, &entity_key, new_key)?;
+        record_journal(&state, &workspace, pack_id, journal)?;
+    }
+    Ok(result)
+}
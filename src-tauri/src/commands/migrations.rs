@@ -0,0 +1,99 @@
+use serde_json::Value;
+use tauri::State;
+
+use crate::commands::entity::{apply_entity_update, reindex_entity};
+use crate::commands::history::record_journal;
+use crate::models::{EntityKey, PackId};
+use crate::services::history::Journal;
+use crate::services::migrations;
+use crate::AppState;
+
+/// Result of migrating deprecated fields or legacy unit values
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrateResult {
+    /// Keys of entities that were changed (after any key change)
+    pub entities_changed: Vec<EntityKey>,
+    /// Total number of fields migrated
+    pub fields_migrated: usize,
+    /// Entities whose migrated JSON failed validation and was not stored
+    pub rejected: Vec<EntityKey>,
+}
+
+/// Rewrite deprecated and obsolete fields to their current form.
+/// Applies to the given entities, or to every entity in the pack.
+#[tauri::command]
+pub fn migrate_deprecated_fields(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_keys: Option<Vec<EntityKey>>,
+) -> Result<MigrateResult, String> {
+    migrate_entities(
+        &state,
+        pack_id,
+        entity_keys,
+        "Migrate deprecated fields",
+        migrations::migrate_entity,
+    )
+}
+
+/// Run `migrate` over the given entities (or every entity in the pack) as
+/// one undoable edit. `migrate` returns how many fields it changed; each
+/// changed entity is stored through `apply_entity_update`, so one that no
+/// longer validates is rejected instead of stored.
+pub fn migrate_entities(
+    state: &AppState,
+    pack_id: PackId,
+    entity_keys: Option<Vec<EntityKey>>,
+    label: &str,
+    migrate: fn(&mut Value) -> usize,
+) -> Result<MigrateResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+
+    let pack = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+
+    if pack.read_only {
+        return Err("Cannot modify entities in a read-only pack".to_string());
+    }
+
+    let keys = entity_keys.unwrap_or_else(|| pack.entities.keys().cloned().collect());
+    let mut updates = Vec::new();
+    for key in keys {
+        let mut json = pack
+            .entities
+            .get(&key)
+            .ok_or_else(|| format!("Entity {} not found", key))?
+            .json
+            .clone();
+        let migrated = migrate(&mut json);
+        if migrated > 0 {
+            let text = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+            updates.push((key, text, migrated));
+        }
+    }
+
+    let mut result = MigrateResult {
+        entities_changed: Vec::new(),
+        fields_migrated: 0,
+        rejected: Vec::new(),
+    };
+
+    let mut journal = Journal::new(label);
+    for (key, text, migrated) in updates {
+        let update = apply_entity_update(&mut workspace, &mut journal, pack_id, &key, &text)?;
+        if update.accepted {
+            let new_key = update.new_key.unwrap_or_else(|| key.clone());
+            reindex_entity(state, &workspace, pack_id, &key, &new_key)?;
+            result.entities_changed.push(new_key);
+            result.fields_migrated += migrated;
+        } else {
+            result.rejected.push(key);
+        }
+    }
+    record_journal(state, &workspace, pack_id, journal)?;
+
+    Ok(result)
+}
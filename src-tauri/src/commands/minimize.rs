@@ -0,0 +1,112 @@
+use serde_json::Value;
+use tauri::State;
+
+use crate::commands::entity::{apply_entity_update, reindex_entity};
+use crate::commands::history::record_journal;
+use crate::models::{EntityKey, PackId, UpdateResult};
+use crate::services::history::Journal;
+use crate::services::inheritance::InheritanceResolver;
+use crate::services::minimize;
+use crate::AppState;
+
+/// Result of minimizing an override
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimizeResult {
+    /// The minimized entity
+    pub json_text: String,
+    /// Top-level fields that are now inherited instead of restated
+    pub inherited: Vec<String>,
+    /// The update, when applied
+    pub update: Option<UpdateResult>,
+}
+
+/// Rewrite an entity that restates a definition from an earlier pack as
+/// `copy-from` plus only the fields that differ. The rewrite is only used
+/// if it resolves to exactly the same JSON as the entity does now.
+/// Without `apply`, the minimized JSON is returned for preview.
+#[tauri::command]
+pub fn minimize_override(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+    apply: bool,
+) -> Result<MinimizeResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+
+    let pack = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+    if pack.read_only {
+        return Err("Cannot modify entities in a read-only pack".to_string());
+    }
+    let entity = pack
+        .entities
+        .get(&entity_key)
+        .ok_or_else(|| format!("Entity {} not found", entity_key))?;
+
+    let resolver = InheritanceResolver::from_workspace(&workspace);
+    let (_, base) = resolver.base(&entity.json, pack_id).ok_or_else(|| {
+        format!(
+            "'{}' doesn't override or copy from an earlier definition",
+            entity.meta.id
+        )
+    })?;
+    let parent_id = entity.meta.copy_from.as_deref().unwrap_or(&entity.meta.id);
+    let resolved = resolver.resolve(&entity.json, pack_id);
+
+    let minimized =
+        minimize::minimize(&entity.json, &resolved, parent_id, &base).map_err(|e| e.to_string())?;
+    // copy-from itself isn't part of the effective definition
+    let effective = |json: &Value| {
+        let mut json = json.clone();
+        if let Value::Object(obj) = &mut json {
+            obj.remove("copy-from");
+        }
+        json
+    };
+    if effective(&resolver.resolve(&minimized, pack_id)) != effective(&resolved) {
+        return Err(format!(
+            "'{}' can't be minimized without changing how it resolves",
+            entity.meta.id
+        ));
+    }
+
+    let inherited = entity
+        .json
+        .as_object()
+        .map(|obj| {
+            obj.keys()
+                .filter(|field| minimized.get(field.as_str()).is_none())
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    let json_text = serde_json::to_string_pretty(&minimized).map_err(|e| e.to_string())?;
+
+    let update = if apply {
+        let mut journal = Journal::new(format!("Minimize {}", entity.meta.id));
+        let result = apply_entity_update(
+            &mut workspace,
+            &mut journal,
+            pack_id,
+            &entity_key,
+            &json_text,
+        )?;
+        if result.accepted {
+            let new_key = result.new_key.as_deref().unwrap_or(&entity_key);
+            reindex_entity(&state, &workspace, pack_id, &entity_key, new_key)?;
+            record_journal(&state, &workspace, pack_id, journal)?;
+        }
+        Some(result)
+    } else {
+        None
+    };
+
+    Ok(MinimizeResult {
+        json_text,
+        inherited,
+        update,
+    })
+}
@@ -1,7 +1,21 @@
+pub mod compare;
+pub mod copy;
+pub mod diff;
 pub mod entity;
 pub mod file;
+pub mod fixes;
+pub mod flags;
+pub mod git;
+pub mod history;
+pub mod ids;
+pub mod mapgen;
+pub mod migrations;
+pub mod minimize;
 pub mod palette;
+pub mod rename;
+pub mod replace;
 pub mod settings;
 pub mod terrain;
 pub mod tileset;
+pub mod units;
 pub mod workspace;
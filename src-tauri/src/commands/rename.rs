@@ -0,0 +1,189 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tauri::State;
+
+use crate::commands::entity::reindex_entity;
+use crate::commands::history::record_journal;
+use crate::models::{EntityKey, PackId};
+use crate::services::history::Journal;
+use crate::services::ids::id_namespace;
+use crate::services::references::{self, Reference};
+use crate::AppState;
+
+/// A place that refers to a renamed entity
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceSite {
+    pub pack_id: PackId,
+    pub pack_name: String,
+    /// Key of the referring entity (after any key change)
+    pub entity_key: EntityKey,
+    pub path: String,
+}
+
+/// Result of renaming an entity
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameResult {
+    pub new_key: EntityKey,
+    /// References rewritten to the new ID
+    pub updated: Vec<ReferenceSite>,
+    /// References in read-only packs that still use the old ID
+    pub not_updated: Vec<ReferenceSite>,
+    /// Key of the MIGRATION entry added for the old ID, if requested
+    pub migration_key: Option<EntityKey>,
+}
+
+/// Change an entity's ID and rewrite every reference to it in editable packs.
+/// With `add_migration`, a MIGRATION entry for the old ID is added next to the
+/// entity so existing saves keep their items (items only).
+#[tauri::command]
+pub fn rename_entity(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_key: EntityKey,
+    new_id: String,
+    add_migration: bool,
+) -> Result<RenameResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+
+    let new_id = new_id.trim().to_string();
+    if new_id.is_empty() {
+        return Err("New ID is empty".to_string());
+    }
+
+    let pack = workspace
+        .packs
+        .get(&pack_id)
+        .ok_or_else(|| format!("Pack {} not found", pack_id))?;
+    if pack.read_only {
+        return Err("Cannot modify entities in a read-only pack".to_string());
+    }
+    let entity = pack
+        .entities
+        .get(&entity_key)
+        .ok_or_else(|| format!("Entity {} not found", entity_key))?;
+
+    let old_id = entity.meta.id.clone();
+    let namespace = id_namespace(&entity.meta.entity_type).to_string();
+    let source_file = entity.source_file.clone();
+    if new_id == old_id {
+        return Err(format!("'{}' already has that ID", old_id));
+    }
+    let id_field = ["id", "abstract"]
+        .into_iter()
+        .find(|field| entity.json.get(*field).and_then(|v| v.as_str()) == Some(old_id.as_str()))
+        .ok_or_else(|| {
+            format!(
+                "'{}' can't be renamed: its ID comes from another field",
+                old_id
+            )
+        })?;
+    if add_migration && namespace != "item" {
+        return Err("Migration entries are only supported for items".to_string());
+    }
+
+    // Refuse anything that would change which definition an ID resolves to
+    for other in workspace.packs.values() {
+        for (key, e) in &other.entities {
+            if id_namespace(&e.meta.entity_type) != namespace {
+                continue;
+            }
+            if e.meta.id == new_id {
+                return Err(format!("'{}' is already defined in {}", new_id, other.name));
+            }
+            if e.meta.id == old_id && (other.id != pack_id || *key != entity_key) {
+                return Err(format!(
+                    "'{}' is also defined in {}; rename would change which definition references use",
+                    old_id, other.name
+                ));
+            }
+        }
+    }
+
+    // Find references everywhere before changing anything
+    let mut found: Vec<(PackId, EntityKey, Vec<Reference>)> = Vec::new();
+    for (other_id, other) in &workspace.packs {
+        let mut keys: Vec<&EntityKey> = other.entities.keys().collect();
+        keys.sort();
+        for key in keys {
+            if *other_id == pack_id && *key == entity_key {
+                continue;
+            }
+            let refs = references::find_references(&other.entities[key].json, &namespace, &old_id);
+            if !refs.is_empty() {
+                found.push((*other_id, key.clone(), refs));
+            }
+        }
+    }
+
+    let mut result = RenameResult {
+        new_key: entity_key.clone(),
+        updated: Vec::new(),
+        not_updated: Vec::new(),
+        migration_key: None,
+    };
+
+    // One transaction per pack, so each pack's history can undo its part
+    let label = format!("Rename {} to {}", old_id, new_id);
+    let mut journals: HashMap<PackId, Journal> = HashMap::new();
+
+    for (other_id, key, refs) in found {
+        let other = workspace.packs.get_mut(&other_id).unwrap();
+        let pack_name = other.name.clone();
+        let sites = |entity_key: &EntityKey| {
+            refs.iter()
+                .map(|r| ReferenceSite {
+                    pack_id: other_id,
+                    pack_name: pack_name.clone(),
+                    entity_key: entity_key.clone(),
+                    path: r.path.clone(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        if other.read_only {
+            result.not_updated.extend(sites(&key));
+            continue;
+        }
+
+        let mut json = other.entities[&key].json.clone();
+        references::rewrite_references(&mut json, &refs, &new_id);
+        let journal = journals
+            .entry(other_id)
+            .or_insert_with(|| Journal::new(label.clone()));
+        let new_key = journal.set_entity_json(other, &key, json)?;
+        result.updated.extend(sites(&new_key));
+        reindex_entity(&state, &workspace, other_id, &key, &new_key)?;
+    }
+
+    let journal = journals
+        .entry(pack_id)
+        .or_insert_with(|| Journal::new(label.clone()));
+    let pack = workspace.packs.get_mut(&pack_id).unwrap();
+    let mut json = pack.entities[&entity_key].json.clone();
+    json[id_field] = Value::String(new_id.clone());
+    result.new_key = journal.set_entity_json(pack, &entity_key, json)?;
+    reindex_entity(&state, &workspace, pack_id, &entity_key, &result.new_key)?;
+
+    if add_migration {
+        let migration = json!({
+            "type": "MIGRATION",
+            "id": old_id,
+            "replace": new_id,
+        });
+        let pack = workspace.packs.get_mut(&pack_id).unwrap();
+        let key = journals
+            .get_mut(&pack_id)
+            .unwrap()
+            .add_entity(pack, migration, source_file)?;
+        reindex_entity(&state, &workspace, pack_id, &key, &key)?;
+        result.migration_key = Some(key);
+    }
+
+    for (pack_id, journal) in journals {
+        record_journal(&state, &workspace, pack_id, journal)?;
+    }
+
+    Ok(result)
+}
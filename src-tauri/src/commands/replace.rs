@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use tauri::State;
+
+use crate::commands::entity::{apply_entity_update, reindex_entity};
+use crate::commands::history::record_journal;
+use crate::models::{
+    AcceptedChanges, ContentPack, EntityChanges, EntityKey, PackId, ReplaceQuery, Workspace,
+};
+use crate::services::history::Journal;
+use crate::services::replace::{self, Replacer};
+use crate::AppState;
+
+/// Result of applying accepted find-and-replace changes
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceResult {
+    /// Keys of entities that were changed (after any key change)
+    pub entities_changed: Vec<EntityKey>,
+    /// Total number of string values replaced
+    pub values_replaced: usize,
+    /// Entities whose new JSON failed validation and was not stored
+    pub rejected: Vec<EntityKey>,
+}
+
+/// Find string values matching a query across editable packs and list what
+/// replacing them would change, without changing anything.
+/// Read-only packs are skipped, or refused if asked for explicitly.
+#[tauri::command]
+pub fn preview_replace(
+    state: State<'_, AppState>,
+    query: ReplaceQuery,
+    pack_ids: Option<Vec<PackId>>,
+    entity_types: Option<Vec<String>>,
+) -> Result<Vec<EntityChanges>, String> {
+    let workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let replacer = Replacer::new(&query).map_err(|e| e.to_string())?;
+
+    let mut previews = Vec::new();
+    for pack in target_packs(&workspace, pack_ids.as_deref())? {
+        let mut keys: Vec<&EntityKey> = pack.entities.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let entity = &pack.entities[key];
+            if let Some(ref types) = entity_types {
+                if !types.contains(&entity.meta.entity_type) {
+                    continue;
+                }
+            }
+
+            let changes = replacer.changes(&entity.json);
+            if !changes.is_empty() {
+                previews.push(EntityChanges {
+                    pack_id: pack.id,
+                    pack_name: pack.name.clone(),
+                    entity_key: key.clone(),
+                    entity_id: entity.meta.id.clone(),
+                    entity_type: entity.meta.entity_type.clone(),
+                    changes,
+                });
+            }
+        }
+    }
+
+    Ok(previews)
+}
+
+/// Apply the accepted changes from a preview in one step.
+/// Changes are recomputed from the current JSON, so values edited since the
+/// preview are left alone. Each entity is validated and dirty-tracked like
+/// `update_entity`.
+#[tauri::command]
+pub fn apply_replace(
+    state: State<'_, AppState>,
+    query: ReplaceQuery,
+    accepted: Vec<AcceptedChanges>,
+) -> Result<ReplaceResult, String> {
+    let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+    let replacer = Replacer::new(&query).map_err(|e| e.to_string())?;
+
+    // Check everything up front so nothing is half-applied
+    let mut updates = Vec::new();
+    for accepted in &accepted {
+        let pack = workspace
+            .packs
+            .get(&accepted.pack_id)
+            .ok_or_else(|| format!("Pack {} not found", accepted.pack_id))?;
+        if pack.read_only {
+            return Err(format!(
+                "Cannot modify entities in read-only pack {}",
+                pack.name
+            ));
+        }
+        let entity = pack
+            .entities
+            .get(&accepted.entity_key)
+            .ok_or_else(|| format!("Entity {} not found", accepted.entity_key))?;
+
+        let changes: Vec<_> = replacer
+            .changes(&entity.json)
+            .into_iter()
+            .filter(|change| accepted.paths.contains(&change.path))
+            .collect();
+
+        let mut json = entity.json.clone();
+        let applied = replace::apply_changes(&mut json, &changes);
+        if applied > 0 {
+            let text = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+            updates.push((accepted.pack_id, accepted.entity_key.clone(), text, applied));
+        }
+    }
+
+    let mut result = ReplaceResult {
+        entities_changed: Vec::new(),
+        values_replaced: 0,
+        rejected: Vec::new(),
+    };
+
+    // One transaction per pack, so each pack's history can undo its part
+    let mut journals: HashMap<PackId, Journal> = HashMap::new();
+    for (pack_id, key, text, applied) in updates {
+        let journal = journals
+            .entry(pack_id)
+            .or_insert_with(|| Journal::new(format!("Replace '{}'", query.find)));
+        let update = apply_entity_update(&mut workspace, journal, pack_id, &key, &text)?;
+        if update.accepted {
+            let new_key = update.new_key.unwrap_or_else(|| key.clone());
+            reindex_entity(&state, &workspace, pack_id, &key, &new_key)?;
+            result.entities_changed.push(new_key);
+            result.values_replaced += applied;
+        } else {
+            result.rejected.push(key);
+        }
+    }
+    for (pack_id, journal) in journals {
+        record_journal(&state, &workspace, pack_id, journal)?;
+    }
+
+    Ok(result)
+}
+
+/// Editable packs to search, in load order
+fn target_packs<'a>(
+    workspace: &'a Workspace,
+    pack_ids: Option<&[PackId]>,
+) -> Result<Vec<&'a ContentPack>, String> {
+    match pack_ids {
+        Some(ids) => ids
+            .iter()
+            .map(|id| {
+                let pack = workspace
+                    .packs
+                    .get(id)
+                    .ok_or_else(|| format!("Pack {} not found", id))?;
+                if pack.read_only {
+                    return Err(format!(
+                        "Cannot modify entities in read-only pack {}",
+                        pack.name
+                    ));
+                }
+                Ok(pack)
+            })
+            .collect(),
+        None => Ok(workspace
+            .load_order
+            .iter()
+            .filter_map(|id| workspace.packs.get(id))
+            .filter(|pack| !pack.read_only)
+            .collect()),
+    }
+}
@@ -14,6 +14,9 @@ pub struct AppSettings {
     /// Additional directories containing mods
     #[serde(default)]
     pub mod_directories: Vec<String>,
+    /// Required ID prefix for mod content; `{mod_id}` is replaced with the pack's mod ID
+    #[serde(default)]
+    pub id_prefix_pattern: Option<String>,
 }
 
 /// Get the settings file path
@@ -0,0 +1,24 @@
+use tauri::State;
+
+use crate::commands::migrations::{migrate_entities, MigrateResult};
+use crate::models::{EntityKey, PackId};
+use crate::services::units;
+use crate::AppState;
+
+/// Convert legacy integer volume, weight, price, etc. to unit strings.
+/// Applies to the given entities, or to every entity in the pack.
+/// Each converted value counts as one migrated field in the result.
+#[tauri::command]
+pub fn normalize_units(
+    state: State<'_, AppState>,
+    pack_id: PackId,
+    entity_keys: Option<Vec<EntityKey>>,
+) -> Result<MigrateResult, String> {
+    migrate_entities(
+        &state,
+        pack_id,
+        entity_keys,
+        "Normalize units",
+        units::normalize_legacy_values,
+    )
+}
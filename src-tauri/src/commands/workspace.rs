@@ -37,6 +37,8 @@ pub async fn load_content_pack(
 
     {
         let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+        let mut index = state.search_index.lock().map_err(|e| e.to_string())?;
+        index.index_pack(&pack);
         workspace.packs.insert(result.pack_id, pack);
         workspace.load_order.push(result.pack_id);
     }
@@ -77,6 +79,16 @@ pub fn close_pack(
     // Remove from workspace
     workspace.packs.remove(&pack_id);
     workspace.load_order.retain(|id| *id != pack_id);
+    state
+        .search_index
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove_pack(pack_id);
+    state
+        .history
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&pack_id);
 
     Ok(())
 }
@@ -102,11 +114,20 @@ pub async fn reload_pack(
         .map_err(|e| e.to_string())?;
 
     // Create the full pack and replace it
-    let pack = loader::create_pack_from_result(&result, &path, read_only, None, None, false);
+    let mut pack = loader::create_pack_from_result(&result, &path, read_only, None, None, false);
+    pack.id = pack_id;
 
     {
         let mut workspace = state.workspace.lock().map_err(|e| e.to_string())?;
+        let mut index = state.search_index.lock().map_err(|e| e.to_string())?;
+        index.index_pack(&pack);
         workspace.packs.insert(pack_id, pack);
+        // Reloading discards unsaved changes, so their history goes too
+        state
+            .history
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(&pack_id);
     }
 
     // Return result with original pack_id
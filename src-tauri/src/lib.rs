@@ -1,20 +1,40 @@
+use std::collections::HashMap;
 use std::sync::Mutex;
 
 mod commands;
 mod models;
 mod services;
 
+use models::{EntityKey, PackId};
 pub use models::Workspace;
+use services::history::PackHistory;
+use services::palette::PaletteCatalog;
+use services::search_index::SearchIndex;
 
 /// Application state shared across all commands
 pub struct AppState {
     pub workspace: Mutex<Workspace>,
+    /// Full-text index over loaded entities.
+    /// Always locked after `workspace` when both are needed.
+    pub search_index: Mutex<SearchIndex>,
+    /// Recently opened entities, most recent last
+    pub recent_entities: Mutex<Vec<(PackId, EntityKey)>>,
+    /// Undo/redo history of each editable pack.
+    /// Always locked after `workspace` when both are needed.
+    pub history: Mutex<HashMap<PackId, PackHistory>>,
+    /// Palettes from the game's data directory.
+    /// Never locked while `workspace` is held, since a scan can take a while.
+    pub palette_catalog: Mutex<PaletteCatalog>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
             workspace: Mutex::new(Workspace::default()),
+            search_index: Mutex::new(SearchIndex::default()),
+            recent_entities: Mutex::new(Vec::new()),
+            history: Mutex::new(HashMap::new()),
+            palette_catalog: Mutex::new(PaletteCatalog::default()),
         }
     }
 }
@@ -38,8 +58,12 @@ pub fn run() {
             commands::entity::get_entity,
             commands::entity::update_entity,
             commands::entity::search_entities,
+            commands::entity::full_text_search,
             // File commands
             commands::file::save_pack,
+            commands::file::move_entities,
+            commands::file::split_file_by_type,
+            commands::file::merge_files,
             // Settings commands
             commands::settings::get_settings,
             commands::settings::save_settings,
@@ -53,6 +77,42 @@ pub fn run() {
             commands::terrain::list_furniture_types,
             // Palette commands
             commands::palette::load_palette,
+            // Mapgen commands
+            commands::mapgen::get_mapgen,
+            commands::mapgen::update_mapgen,
+            // Unit commands
+            commands::units::normalize_units,
+            // Flag commands
+            commands::flags::list_known_flags,
+            // Migration commands
+            commands::migrations::migrate_deprecated_fields,
+            // Fix commands
+            commands::fixes::apply_fix,
+            commands::fixes::apply_fixes_by_code,
+            // ID commands
+            commands::ids::check_pack_ids,
+            // Find and replace commands
+            commands::replace::preview_replace,
+            commands::replace::apply_replace,
+            // Rename commands
+            commands::rename::rename_entity,
+            // Copy commands
+            commands::copy::copy_entity_to_pack,
+            // Minimize commands
+            commands::minimize::minimize_override,
+            // History commands
+            commands::history::undo,
+            commands::history::redo,
+            commands::history::get_history_state,
+            // Diff commands
+            commands::diff::diff_entity,
+            commands::diff::revert_entity_field,
+            commands::compare::compare_pack_versions,
+            // Git commands
+            commands::git::get_pack_git_status,
+            commands::git::get_committed_entity,
+            commands::git::blame_entity,
+            commands::git::revert_entity_to_head,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{EntityKey, PackId};
+
+/// What an entity is compared against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffBase {
+    /// The entity as it is in its source file
+    Disk,
+    /// The definition it overrides or copies from in an earlier pack
+    Base,
+    /// The entity as it is in the pack's last git commit
+    Head,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A value that differs between the base and the current JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffEntry {
+    /// JSON path of the value (e.g., "$.melee_damage.bash")
+    pub path: String,
+    pub kind: DiffKind,
+    /// Value in the base (None when added)
+    pub before: Option<Value>,
+    /// Current value (None when removed)
+    pub after: Option<Value>,
+}
+
+/// Differences between an entity and what it is compared against
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityDiff {
+    pub base: DiffBase,
+    /// Pack the base definition comes from (for `Base`)
+    pub base_pack_name: Option<String>,
+    /// Whether there was anything to compare against (false for a new entity
+    /// or one with no earlier definition; everything then shows as added)
+    pub has_base: bool,
+    pub entries: Vec<DiffEntry>,
+}
+
+/// Entity in both versions of a pack whose JSON differs
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedEntity {
+    pub id: String,
+    pub entries: Vec<DiffEntry>,
+}
+
+/// Differences between two versions of a pack for one entity type
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeChanges {
+    pub entity_type: String,
+    /// IDs only in the new version
+    pub added: Vec<String>,
+    /// IDs only in the old version
+    pub removed: Vec<String>,
+    pub changed: Vec<ChangedEntity>,
+}
+
+/// A reference from a loaded editable pack to an ID the new version removed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenReference {
+    /// The removed ID
+    pub id: String,
+    pub pack_id: PackId,
+    pub pack_name: String,
+    pub entity_key: EntityKey,
+    pub path: String,
+}
+
+/// Comparison of two versions of a pack (e.g. two game checkouts)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionReport {
+    pub old_name: String,
+    pub new_name: String,
+    /// Types with any differences, sorted by type
+    pub types: Vec<TypeChanges>,
+    pub broken_references: Vec<BrokenReference>,
+}
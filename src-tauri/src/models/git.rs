@@ -0,0 +1,52 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// A file in a pack with uncommitted or unsaved changes
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStatus {
+    /// Path relative to the pack root
+    pub path: PathBuf,
+    /// Has changes added to the index
+    pub staged: bool,
+    /// Has changes in the work tree that aren't staged
+    pub modified: bool,
+    /// Not tracked by git
+    pub untracked: bool,
+    /// Has edits in Catalyst that haven't been saved
+    pub unsaved: bool,
+}
+
+/// Git state of a pack merged with Catalyst's own dirty tracking
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackGitStatus {
+    /// Whether the pack is inside a git work tree.
+    /// When false, `files` only lists files with unsaved edits.
+    pub is_repository: bool,
+    /// Current branch (None when HEAD is detached or outside a repository)
+    pub branch: Option<String>,
+    pub files: Vec<FileStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitInfo {
+    pub hash: String,
+    pub author: String,
+    /// Author date in ISO 8601 format
+    pub date: String,
+    /// First line of the commit message
+    pub summary: String,
+}
+
+/// Which commit last changed an entity
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityBlame {
+    /// None when the entity was never committed, or its last change is
+    /// older than the commits searched
+    pub last_commit: Option<CommitInfo>,
+    /// Whether the entity differs from the last commit (saved or not)
+    pub uncommitted: bool,
+}
@@ -0,0 +1,375 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Size of one overmap tile in map tiles
+pub const OMT_SIZE: usize = 24;
+
+/// Symbol -> what it places (an ID, a weighted list, a parameter, or an
+/// object with extra options, depending on the section)
+pub type SymbolMap = Map<String, Value>;
+
+/// A section of a mapgen object or palette that maps row symbols to content
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolSection {
+    pub name: &'static str,
+    /// ID namespace of a bare or weighted ID entry, for sections that take one
+    pub namespace: Option<&'static str>,
+    /// Fields of object entries that name content, with the ID namespace of
+    /// each. Dotted fields reach into nested objects.
+    pub fields: &'static [(&'static str, &'static str)],
+}
+
+const fn section(
+    name: &'static str,
+    namespace: Option<&'static str>,
+    fields: &'static [(&'static str, &'static str)],
+) -> SymbolSection {
+    SymbolSection {
+        name,
+        namespace,
+        fields,
+    }
+}
+
+/// Every symbol section the game reads from mapgen and palettes
+pub const SYMBOL_SECTIONS: &[SymbolSection] = &[
+    section("terrain", Some("terrain"), &[]),
+    section("furniture", Some("furniture"), &[]),
+    section("traps", Some("trap"), &[("trap", "trap")]),
+    section("items", None, &[("item", "item_group")]),
+    section("item", None, &[("item", "item")]),
+    section("monsters", None, &[("monster", "monstergroup")]),
+    section("monster", None, &[("monster", "MONSTER")]),
+    section("vehicles", None, &[("vehicle", "vehicle_group")]),
+    section("toilets", None, &[]),
+    section("liquids", None, &[("liquid", "item")]),
+    section(
+        "nested",
+        None,
+        &[("chunks", "mapgen"), ("else_chunks", "mapgen")],
+    ),
+    section("signs", None, &[("snippet", "snippet")]),
+    section("fields", None, &[("field", "field_type")]),
+    section("npcs", None, &[("class", "npc_class")]),
+    section("vendingmachines", None, &[("item_group", "item_group")]),
+    section("gaspumps", None, &[("fuel", "item")]),
+    section(
+        "sealed_item",
+        None,
+        &[
+            ("item.item", "item"),
+            ("items.item", "item_group"),
+            ("furniture", "furniture"),
+        ],
+    ),
+    section("graffiti", None, &[("snippet", "snippet")]),
+    section("rubble", None, &[("rubble_type", "furniture")]),
+    section(
+        "mapping",
+        None,
+        &[("terrain", "terrain"), ("furniture", "furniture")],
+    ),
+    section("computers", None, &[]),
+    section("zones", None, &[]),
+    section(
+        "ter_furn_transforms",
+        None,
+        &[("transform", "ter_furn_transform")],
+    ),
+    section("faction_owner", None, &[("id", "faction")]),
+];
+
+/// A `"type": "mapgen"` entity.
+///
+/// Fields the model doesn't know are kept in `extra`, so converting to and
+/// from JSON never loses anything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mapgen {
+    #[serde(rename = "type")]
+    pub entity_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub om_terrain: Option<OmTerrain>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nested_mapgen_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_mapgen_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object: Option<MapgenObject>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The overmap terrain(s) a mapgen is for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OmTerrain {
+    Single(String),
+    /// Several terrains that each use this same 24x24 map
+    List(Vec<String>),
+    /// Rows of terrains covered by one large map
+    Grid(Vec<Vec<String>>),
+}
+
+/// What kind of mapgen an entity is, from the ID field it uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapgenKind {
+    /// Generates overmap terrain (`om_terrain`)
+    Terrain,
+    /// Placed inside other maps (`nested_mapgen_id`)
+    Nested,
+    /// Applied on top of an existing map (`update_mapgen_id`)
+    Update,
+}
+
+/// The `object` of a JSON mapgen
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MapgenObject {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill_ter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<Vec<String>>,
+    /// Size in tiles of nested and update mapgen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mapgensize: Option<[usize; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palettes: Option<Vec<PaletteRef>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terrain: Option<SymbolMap>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub furniture: Option<SymbolMap>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<SymbolMap>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monsters: Option<SymbolMap>,
+    /// Nested mapgen placed by symbol
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nested: Option<SymbolMap>,
+    /// Every `place_*` section (e.g. `place_items`), keyed by its full name
+    #[serde(skip)]
+    pub placements: BTreeMap<String, Vec<Placement>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An entry of the `palettes` list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PaletteRef {
+    Id(String),
+    /// A choice made when the map is generated (`param` or `distribution`)
+    Parameterized(Map<String, Value>),
+}
+
+/// An entry of a `place_*` section, placed by coordinates instead of symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Placement {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    /// What to place and how (`item`, `chance`, `repeat`, ...)
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Coordinate {
+    Fixed(i64),
+    /// Random position between the bounds, inclusive
+    Range(Vec<i64>),
+}
+
+impl Mapgen {
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        if json.get("type").and_then(|v| v.as_str()) != Some("mapgen") {
+            return Err("Not a mapgen entity".to_string());
+        }
+        let mut mapgen: Mapgen = serde_json::from_value(json.clone()).map_err(|e| e.to_string())?;
+
+        if let Some(object) = &mut mapgen.object {
+            let names: Vec<String> = object
+                .extra
+                .keys()
+                .filter(|key| key.starts_with("place_"))
+                .cloned()
+                .collect();
+            for name in names {
+                let value = object.extra.remove(&name).unwrap();
+                let placements = serde_json::from_value(value)
+                    .map_err(|e| format!("Invalid {}: {}", name, e))?;
+                object.placements.insert(name, placements);
+            }
+        }
+        Ok(mapgen)
+    }
+
+    pub fn to_json(&self) -> Result<Value, String> {
+        let mut json = serde_json::to_value(self).map_err(|e| e.to_string())?;
+
+        if let (Some(object), Some(Value::Object(out))) = (&self.object, json.get_mut("object")) {
+            for (name, placements) in &object.placements {
+                let value = serde_json::to_value(placements).map_err(|e| e.to_string())?;
+                out.insert(name.clone(), value);
+            }
+        }
+        Ok(json)
+    }
+
+    pub fn kind(&self) -> MapgenKind {
+        if self.nested_mapgen_id.is_some() {
+            MapgenKind::Nested
+        } else if self.update_mapgen_id.is_some() {
+            MapgenKind::Update
+        } else {
+            MapgenKind::Terrain
+        }
+    }
+
+    /// Map size in tiles (width, height), if it can be told
+    pub fn size(&self) -> Option<(usize, usize)> {
+        if let Some([width, height]) = self.object.as_ref().and_then(|o| o.mapgensize) {
+            return Some((width, height));
+        }
+        if self.kind() != MapgenKind::Terrain {
+            return None;
+        }
+        let (columns, rows) = self.om_terrain.as_ref()?.shape();
+        Some((OMT_SIZE * columns, OMT_SIZE * rows))
+    }
+}
+
+impl OmTerrain {
+    /// Every terrain ID, row by row
+    pub fn ids(&self) -> Vec<&str> {
+        match self {
+            OmTerrain::Single(id) => vec![id.as_str()],
+            OmTerrain::List(ids) => ids.iter().map(String::as_str).collect(),
+            OmTerrain::Grid(rows) => rows.iter().flatten().map(String::as_str).collect(),
+        }
+    }
+
+    /// Overmap tiles covered (columns, rows)
+    pub fn shape(&self) -> (usize, usize) {
+        match self {
+            OmTerrain::Single(_) | OmTerrain::List(_) => (1, 1),
+            OmTerrain::Grid(rows) => (rows.first().map_or(0, Vec::len), rows.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assert_round_trip(json: Value) {
+        let mapgen = Mapgen::from_json(&json).unwrap();
+        assert_eq!(mapgen.to_json().unwrap(), json);
+    }
+
+    #[test]
+    fn om_terrain_forms_round_trip() {
+        assert_round_trip(json!({
+            "type": "mapgen",
+            "method": "json",
+            "om_terrain": "house_01",
+            "object": { "fill_ter": "t_floor", "rows": ["."] }
+        }));
+        assert_round_trip(json!({
+            "type": "mapgen",
+            "method": "json",
+            "om_terrain": ["field", "forest"],
+            "weight": 250,
+            "object": { "fill_ter": "t_grass" }
+        }));
+        assert_round_trip(json!({
+            "type": "mapgen",
+            "method": "json",
+            "om_terrain": [["mall_a_1", "mall_a_2"], ["mall_a_3", "mall_a_4"]],
+            "object": { "fill_ter": "t_floor" }
+        }));
+    }
+
+    #[test]
+    fn nested_and_update_mapgen_round_trip() {
+        assert_round_trip(json!({
+            "type": "mapgen",
+            "method": "json",
+            "nested_mapgen_id": "desk_chunk",
+            "object": {
+                "mapgensize": [2, 2],
+                "rows": ["hh", "  "],
+                "furniture": { "h": "f_desk" }
+            }
+        }));
+        assert_round_trip(json!({
+            "type": "mapgen",
+            "method": "json",
+            "update_mapgen_id": "bandit_camp_update",
+            "object": { "place_monster": [{ "monster": "mon_bandit", "x": 3, "y": 4 }] }
+        }));
+    }
+
+    #[test]
+    fn symbol_sections_and_palettes_round_trip() {
+        assert_round_trip(json!({
+            "type": "mapgen",
+            "method": "json",
+            "om_terrain": "store",
+            "object": {
+                "rows": ["#+#"],
+                "palettes": [
+                    "standard_domestic_palette",
+                    { "param": "roof_type" },
+                    { "distribution": [["palette_a", 3], ["palette_b", 1]] }
+                ],
+                "terrain": { "#": "t_wall", "+": ["t_door_c", "t_door_locked"] },
+                "furniture": { "h": "f_chair" },
+                "items": { "#": { "item": "shelves", "chance": 30 } },
+                "monsters": { "+": { "monster": "GROUP_ZOMBIE", "chance": 10 } },
+                "nested": { "N": { "chunks": [["desk_chunk", 1], ["null", 3]] } }
+            }
+        }));
+    }
+
+    #[test]
+    fn place_sections_round_trip() {
+        assert_round_trip(json!({
+            "type": "mapgen",
+            "method": "json",
+            "om_terrain": "garage",
+            "object": {
+                "fill_ter": "t_concrete",
+                "place_items": [
+                    { "item": "tools_home", "x": [2, 5], "y": 3, "chance": 50, "repeat": [1, 2] }
+                ],
+                "place_monsters": [{ "monster": "GROUP_ZOMBIE", "x": 0, "y": [0, 23], "density": 0.1 }],
+                "place_nested": [{ "chunks": ["desk_chunk"], "x": 10, "y": 10 }],
+                "place_vehicles": [{ "vehicle": "parking_garage", "x": 12, "y": 12, "rotation": 270 }]
+            }
+        }));
+    }
+
+    #[test]
+    fn unknown_fields_round_trip() {
+        assert_round_trip(json!({
+            "type": "mapgen",
+            "method": "json",
+            "om_terrain": "lab",
+            "//": "comment",
+            "flags": ["ERASE_ALL_BEFORE_PLACING_TERRAIN"],
+            "object": {
+                "fill_ter": "t_floor",
+                "predecessor_mapgen": "field",
+                "traps": { "^": "tr_beartrap" },
+                "computers": { "6": { "name": "Lab Terminal", "options": [] } }
+            }
+        }));
+        assert_round_trip(json!({ "type": "mapgen", "om_terrain": "empty", "weight": 0 }));
+    }
+}
@@ -1,7 +1,19 @@
+pub mod diff;
 pub mod entity;
+pub mod git;
+pub mod mapgen;
+pub mod palette;
+pub mod replace;
+pub mod rules;
 pub mod validation;
 pub mod workspace;
 
+pub use diff::*;
 pub use entity::*;
+pub use git::*;
+pub use mapgen::*;
+pub use palette::*;
+pub use replace::*;
+pub use rules::*;
 pub use validation::*;
 pub use workspace::*;
@@ -0,0 +1,55 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// A palette with every palette it includes resolved
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteData {
+    pub id: String,
+    pub mappings: Vec<SymbolMapping>,
+    /// Every palette applied before this one's own definitions, including
+    /// nested ones and each palette a parameter can choose, in order
+    pub includes: Vec<String>,
+    /// Included palettes that couldn't be found
+    pub missing: Vec<String>,
+}
+
+/// Everything a palette places for one symbol
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolMapping {
+    pub symbol: String,
+    /// First candidate of the terrain definition that wins
+    pub terrain: Option<String>,
+    /// First candidate of the furniture definition that wins
+    pub furniture: Option<String>,
+    /// Definitions in the order they are applied. For terrain and furniture
+    /// the last one wins; every definition in other sections is placed.
+    pub definitions: Vec<SymbolDefinition>,
+}
+
+/// A symbol's entry in one section of one palette
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolDefinition {
+    /// Section it's in (`terrain`, `items`, `nested`, ...)
+    pub section: String,
+    /// Palette that defined it
+    pub palette: String,
+    pub candidates: Vec<SymbolCandidate>,
+    /// The entry as written
+    pub value: Value,
+}
+
+/// One of the things a definition may place
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolCandidate {
+    /// What gets placed, when the entry names it (toilets and signs with
+    /// literal text don't)
+    pub id: Option<String>,
+    /// Relative weight among the candidates of one definition
+    pub weight: i64,
+    /// Parameter the candidate is a possible value of
+    pub parameter: Option<String>,
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use super::{EntityKey, PackId};
+
+/// What to find and what to replace it with, across entity string values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceQuery {
+    /// Text to find, or a regex when `regex` is set
+    pub find: String,
+    /// Replacement; with `regex`, `$1` / `${name}` insert capture groups
+    pub replace: String,
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// JSONPath limiting where to look (e.g., "$.description"); the whole entity if unset
+    pub path: Option<String>,
+}
+
+/// One string value that would change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposedChange {
+    /// JSON path of the value (e.g., "$.use_action[0].msg")
+    pub path: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// The proposed changes to one entity
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityChanges {
+    pub pack_id: PackId,
+    pub pack_name: String,
+    pub entity_key: EntityKey,
+    pub entity_id: String,
+    pub entity_type: String,
+    pub changes: Vec<ProposedChange>,
+}
+
+/// Changes the user accepted for one entity, by path
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptedChanges {
+    pub pack_id: PackId,
+    pub entity_key: EntityKey,
+    pub paths: Vec<String>,
+}
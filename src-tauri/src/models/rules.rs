@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A team-defined validation rule from a pack's rule file.
+///
+/// The rule selects values with a JSONPath and reports a warning with its own
+/// code and message when a check fails. At least one check must be set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationRule {
+    /// Warning code reported when the rule fails (e.g., "MYMOD_NO_DESCRIPTION")
+    pub code: String,
+    /// Warning message; `{id}`, `{type}`, `{path}`, `{value}` and `{mod_id}` are substituted
+    pub message: String,
+    /// Entity types the rule applies to (empty means every type)
+    #[serde(default)]
+    pub types: Vec<String>,
+    /// JSONPath selecting the values to check (e.g., "$.description")
+    pub path: String,
+    /// The path must select at least one value (skipped for copy-from entities)
+    #[serde(default)]
+    pub required: bool,
+    /// The path must not select anything
+    #[serde(default)]
+    pub forbidden: bool,
+    /// Regex every selected value must match; `{mod_id}` is substituted
+    pub pattern: Option<String>,
+    /// Values every selected value must be one of
+    pub one_of: Option<Vec<Value>>,
+}
@@ -1,4 +1,5 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct ValidationResult {
@@ -17,6 +18,8 @@ pub struct ValidationError {
     pub path: Option<String>,
     /// Line number if available
     pub line: Option<usize>,
+    /// Machine-applicable fix, if the validator knows one
+    pub fix: Option<Fix>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -27,6 +30,10 @@ pub struct ValidationWarning {
     pub message: String,
     /// JSON path to the issue
     pub path: Option<String>,
+    /// Line number if available
+    pub line: Option<usize>,
+    /// Machine-applicable fix, if the validator knows one
+    pub fix: Option<Fix>,
 }
 
 impl ValidationResult {
@@ -47,6 +54,7 @@ impl ValidationResult {
                 message: message.into(),
                 path: None,
                 line: None,
+                fix: None,
             }],
             warnings: Vec::new(),
         }
@@ -59,6 +67,7 @@ impl ValidationResult {
             message: message.into(),
             path: None,
             line: None,
+            fix: None,
         });
     }
 
@@ -74,6 +83,7 @@ impl ValidationResult {
             message: message.into(),
             path: Some(path.into()),
             line: None,
+            fix: None,
         });
     }
 
@@ -82,9 +92,57 @@ impl ValidationResult {
             code: code.into(),
             message: message.into(),
             path: None,
+            line: None,
+            fix: None,
+        });
+    }
+
+    pub fn add_warning_with_path(
+        &mut self,
+        code: impl Into<String>,
+        message: impl Into<String>,
+        path: impl Into<String>,
+    ) {
+        self.warnings.push(ValidationWarning {
+            code: code.into(),
+            message: message.into(),
+            path: Some(path.into()),
+            line: None,
+            fix: None,
+        });
+    }
+
+    pub fn add_warning_with_fix(
+        &mut self,
+        code: impl Into<String>,
+        message: impl Into<String>,
+        path: impl Into<String>,
+        fix: Fix,
+    ) {
+        self.warnings.push(ValidationWarning {
+            code: code.into(),
+            message: message.into(),
+            path: Some(path.into()),
+            line: None,
+            fix: Some(fix),
         });
     }
 
+    /// Fixes attached to errors and warnings with the given code
+    pub fn fixes_for_code<'a>(&'a self, code: &'a str) -> impl Iterator<Item = &'a Fix> {
+        let errors = self
+            .errors
+            .iter()
+            .filter(move |e| e.code == code)
+            .filter_map(|e| e.fix.as_ref());
+        let warnings = self
+            .warnings
+            .iter()
+            .filter(move |w| w.code == code)
+            .filter_map(|w| w.fix.as_ref());
+        errors.chain(warnings)
+    }
+
     pub fn merge(&mut self, other: ValidationResult) {
         if !other.valid {
             self.valid = false;
@@ -102,6 +160,7 @@ impl ValidationError {
             message: message.into(),
             path: None,
             line: None,
+            fix: None,
         }
     }
 
@@ -115,3 +174,31 @@ impl ValidationError {
         self
     }
 }
+
+/// A machine-applicable fix for a validation finding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fix {
+    /// Human-readable description (e.g., "Replace 'HAERS' with 'HEARS'")
+    pub description: String,
+    /// JSON Patch operations to apply to the entity
+    pub operations: Vec<PatchOperation>,
+}
+
+/// A JSON Patch (RFC 6902) operation; paths are JSON Pointers (e.g. "/flags/1")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+}
+
+impl Fix {
+    pub fn new(description: impl Into<String>, operations: Vec<PatchOperation>) -> Self {
+        Self {
+            description: description.into(),
+            operations,
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
-use super::entity::Entity;
+use super::entity::{Entity, EntityMeta};
+use super::rules::ValidationRule;
 
 /// Unique identifier for a content pack within this session
 pub type PackId = Uuid;
@@ -36,6 +38,12 @@ pub struct ContentPack {
     pub dirty_files: Vec<PathBuf>,
     /// Metadata about the pack (from modinfo.json if present)
     pub metadata: Option<PackMetadata>,
+    /// Custom validation rules from the pack's rule file
+    pub rules: Vec<ValidationRule>,
+    /// Keys ("type:id") of the entities in each file as last loaded or
+    /// saved, to tell apart entities added to a file on disk since then
+    #[serde(skip)]
+    pub disk_keys: HashMap<PathBuf, HashSet<EntityKey>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -90,6 +98,9 @@ pub struct PackLoadResult {
     pub name: String,
     pub entity_tree: EntityTree,
     pub load_stats: LoadStats,
+    /// Rules from the pack's rule file, read once while loading
+    #[serde(skip)]
+    pub rules: Vec<ValidationRule>,
 }
 
 /// Statistics about a pack load operation
@@ -132,6 +143,8 @@ impl ContentPack {
             entities: HashMap::new(),
             dirty_files: Vec::new(),
             metadata: None,
+            rules: Vec::new(),
+            disk_keys: HashMap::new(),
         }
     }
 
@@ -180,6 +193,88 @@ impl ContentPack {
         EntityTree { by_type, by_file }
     }
 
+    /// Replace an entity's JSON, marking the entity and its file dirty.
+    /// Returns the entity's key afterwards, which changes if its type or ID did.
+    pub fn set_entity_json(&mut self, key: &str, json: Value) -> Result<EntityKey, String> {
+        let meta = EntityMeta::from_json(&json)
+            .ok_or_else(|| "Could not extract entity metadata from JSON".to_string())?;
+        let new_key = format!("{}:{}", meta.entity_type, meta.id);
+
+        let entity = self
+            .entities
+            .get_mut(key)
+            .ok_or_else(|| format!("Entity {} not found", key))?;
+
+        entity.json = json;
+        entity.meta = meta;
+        entity.dirty = true;
+
+        // Track dirty file
+        let source_file = entity.source_file.clone();
+        if !self.dirty_files.contains(&source_file) {
+            self.dirty_files.push(source_file);
+        }
+
+        // Re-insert under the new key if type/id changed
+        if new_key != key {
+            let entity = self.entities.remove(key).unwrap();
+            self.entities.insert(new_key.clone(), entity);
+        }
+
+        Ok(new_key)
+    }
+
+    /// Add a new entity at the end of a source file, marking it and the file dirty
+    pub fn add_entity(&mut self, json: Value, source_file: PathBuf) -> Result<EntityKey, String> {
+        let array_index = self.next_array_index(&source_file);
+        let mut entity = Entity::from_json(json, source_file, array_index)
+            .ok_or_else(|| "Could not extract entity metadata from JSON".to_string())?;
+        let key = entity.key();
+        if self.entities.contains_key(&key) {
+            return Err(format!("Entity {} already exists", key));
+        }
+        entity.dirty = true;
+
+        if !self.dirty_files.contains(&entity.source_file) {
+            self.dirty_files.push(entity.source_file.clone());
+        }
+        self.entities.insert(key.clone(), entity);
+
+        Ok(key)
+    }
+
+    /// Move an entity to the end of another source file, marking it and
+    /// both files dirty. Saving removes it from the old file, and deletes
+    /// the old file if nothing is left in it.
+    pub fn move_entity(&mut self, key: &str, source_file: PathBuf) -> Result<(), String> {
+        let array_index = self.next_array_index(&source_file);
+        let entity = self
+            .entities
+            .get_mut(key)
+            .ok_or_else(|| format!("Entity {} not found", key))?;
+
+        let old_file = std::mem::replace(&mut entity.source_file, source_file.clone());
+        entity.array_index = array_index;
+        entity.dirty = true;
+
+        for file in [old_file, source_file] {
+            if !self.dirty_files.contains(&file) {
+                self.dirty_files.push(file);
+            }
+        }
+        Ok(())
+    }
+
+    /// Position after the last entity in a source file
+    fn next_array_index(&self, source_file: &Path) -> usize {
+        self.entities
+            .values()
+            .filter(|e| e.source_file == source_file)
+            .map(|e| e.array_index + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
     pub fn to_info(&self) -> PackInfo {
         PackInfo {
             id: self.id,
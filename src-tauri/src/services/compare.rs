@@ -0,0 +1,110 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::diff::diff;
+use super::ids::id_namespace;
+use super::references;
+use crate::models::{BrokenReference, ChangedEntity, ContentPack, TypeChanges, Workspace};
+
+/// Added, removed and changed entities between two versions of a pack, by type
+pub fn compare_packs(old: &ContentPack, new: &ContentPack) -> Vec<TypeChanges> {
+    let mut types: BTreeMap<&str, TypeChanges> = BTreeMap::new();
+    for (key, entity) in &old.entities {
+        match new.entities.get(key) {
+            Some(updated) if updated.json != entity.json => {
+                changes(&mut types, &entity.meta.entity_type)
+                    .changed
+                    .push(ChangedEntity {
+                        id: entity.meta.id.clone(),
+                        entries: diff(&entity.json, &updated.json),
+                    });
+            }
+            Some(_) => {}
+            None => changes(&mut types, &entity.meta.entity_type)
+                .removed
+                .push(entity.meta.id.clone()),
+        }
+    }
+    for (key, entity) in &new.entities {
+        if !old.entities.contains_key(key) {
+            changes(&mut types, &entity.meta.entity_type)
+                .added
+                .push(entity.meta.id.clone());
+        }
+    }
+
+    types
+        .into_values()
+        .map(|mut changes| {
+            changes.added.sort();
+            changes.removed.sort();
+            changes.changed.sort_by(|a, b| a.id.cmp(&b.id));
+            changes
+        })
+        .collect()
+}
+
+fn changes<'a, 'b>(
+    types: &'b mut BTreeMap<&'a str, TypeChanges>,
+    entity_type: &'a str,
+) -> &'b mut TypeChanges {
+    types.entry(entity_type).or_insert_with(|| TypeChanges {
+        entity_type: entity_type.to_string(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    })
+}
+
+/// References from editable packs in the workspace to IDs the old version
+/// defines and the new one doesn't (under any type of the same namespace)
+pub fn broken_references(
+    old: &ContentPack,
+    new: &ContentPack,
+    workspace: &Workspace,
+) -> Vec<BrokenReference> {
+    let still_defined: HashSet<(&str, &str)> = new
+        .entities
+        .values()
+        .map(|e| (id_namespace(&e.meta.entity_type), e.meta.id.as_str()))
+        .collect();
+
+    let mut removed: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for entity in old.entities.values() {
+        let namespace = id_namespace(&entity.meta.entity_type);
+        if !still_defined.contains(&(namespace, entity.meta.id.as_str())) {
+            removed
+                .entry(entity.meta.id.as_str())
+                .or_default()
+                .insert(namespace);
+        }
+    }
+    if removed.is_empty() {
+        return Vec::new();
+    }
+
+    let mut broken = Vec::new();
+    for pack_id in &workspace.load_order {
+        let Some(pack) = workspace.packs.get(pack_id).filter(|p| !p.read_only) else {
+            continue;
+        };
+        let mut keys: Vec<_> = pack.entities.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let found = references::find_matching(&pack.entities[key].json, |namespace, id| {
+                removed
+                    .get(id)
+                    .is_some_and(|namespaces| namespaces.contains(namespace))
+            });
+            broken.extend(found.into_iter().map(|reference| BrokenReference {
+                id: reference.id,
+                pack_id: pack.id,
+                pack_name: pack.name.clone(),
+                entity_key: key.clone(),
+                path: reference.path,
+            }));
+        }
+    }
+
+    broken
+}
@@ -0,0 +1,155 @@
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+use super::jsonpath::child_path;
+use crate::models::{ContentPack, DiffEntry, DiffKind, Entity, EntityMeta};
+
+/// Path-based differences between two JSON values.
+///
+/// Objects are compared key by key and arrays index by index when they have
+/// the same length; an array whose length changed is reported as one change,
+/// so each entry can be reverted on its own.
+pub fn diff(before: &Value, after: &Value) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+    diff_at("$".to_string(), before, after, &mut entries);
+    entries
+}
+
+fn diff_at(path: String, before: &Value, after: &Value, entries: &mut Vec<DiffEntry>) {
+    match (before, after) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let path = child_path(&path, key);
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => diff_at(path, a, b, entries),
+                    (Some(a), None) => entries.push(DiffEntry {
+                        path,
+                        kind: DiffKind::Removed,
+                        before: Some(a.clone()),
+                        after: None,
+                    }),
+                    (None, Some(b)) => entries.push(DiffEntry {
+                        path,
+                        kind: DiffKind::Added,
+                        before: None,
+                        after: Some(b.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                diff_at(format!("{}[{}]", path, i), a, b, entries);
+            }
+        }
+        _ if before != after => entries.push(DiffEntry {
+            path,
+            kind: DiffKind::Changed,
+            before: Some(before.clone()),
+            after: Some(after.clone()),
+        }),
+        _ => {}
+    }
+}
+
+/// The entity as it is in its source file, or None if it hasn't been saved yet
+pub fn disk_version(pack: &ContentPack, entity: &Entity) -> Result<Option<Value>, DiffError> {
+    let path = pack.path.join(&entity.source_file);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| DiffError::IoError {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+    let json: Value = serde_json::from_str(&content).map_err(|e| DiffError::ParseError {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+    let Some(array) = json.as_array() else {
+        return Ok(None);
+    };
+    Ok(find_entity(array, entity))
+}
+
+/// An entity's entry in a version of its source file.
+/// The array position is only trusted if it still holds the same entity,
+/// since the type or ID may have been edited since loading.
+pub fn find_entity(array: &[Value], entity: &Entity) -> Option<Value> {
+    let original_key = |value: &Value| {
+        EntityMeta::from_json(value).map(|meta| format!("{}:{}", meta.entity_type, meta.id))
+    };
+    let loaded = array.get(entity.array_index);
+    if let Some(value) = loaded {
+        if original_key(value) == Some(entity.key()) {
+            return Some(value.clone());
+        }
+    }
+    if let Some(value) = array
+        .iter()
+        .find(|value| original_key(value) == Some(entity.key()))
+    {
+        return Some(value.clone());
+    }
+    loaded.filter(|value| value.is_object()).cloned()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DiffError {
+    #[error("IO error reading {path}: {message}")]
+    IoError { path: PathBuf, message: String },
+
+    #[error("Parse error in {path}: {message}")]
+    ParseError { path: PathBuf, message: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn changes(before: Value, after: Value) -> Vec<(String, DiffKind)> {
+        diff(&before, &after)
+            .into_iter()
+            .map(|entry| (entry.path, entry.kind))
+            .collect()
+    }
+
+    #[test]
+    fn nested_fields_by_path() {
+        assert_eq!(
+            changes(
+                json!({ "id": "rock", "melee_damage": { "bash": 5 }, "price": 10 }),
+                json!({ "id": "rock", "melee_damage": { "bash": 7 }, "weight": "1 kg" }),
+            ),
+            [
+                ("$.melee_damage.bash".to_string(), DiffKind::Changed),
+                ("$.price".to_string(), DiffKind::Removed),
+                ("$.weight".to_string(), DiffKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays_by_index_or_as_a_whole() {
+        assert_eq!(
+            changes(
+                json!({ "flags": ["A", "B"] }),
+                json!({ "flags": ["A", "C"] })
+            ),
+            [("$.flags[1]".to_string(), DiffKind::Changed)]
+        );
+        assert_eq!(
+            changes(json!({ "flags": ["A"] }), json!({ "flags": ["A", "C"] })),
+            [("$.flags".to_string(), DiffKind::Changed)]
+        );
+        assert!(changes(json!({ "flags": ["A"] }), json!({ "flags": ["A"] })).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use serde_json::Value;
+
+use crate::models::{Fix, PatchOperation};
+
+/// Apply a fix's patch operations to entity JSON.
+/// Operations are applied to a copy, so `json` is unchanged on error.
+pub fn apply_fix(json: &mut Value, fix: &Fix) -> Result<(), FixError> {
+    let mut patched = json.clone();
+    for operation in &fix.operations {
+        apply_operation(&mut patched, operation)?;
+    }
+    *json = patched;
+    Ok(())
+}
+
+fn apply_operation(json: &mut Value, operation: &PatchOperation) -> Result<(), FixError> {
+    match operation {
+        PatchOperation::Add { path, value } => add(json, path, value.clone()),
+        PatchOperation::Remove { path } => remove(json, path).map(|_| ()),
+        PatchOperation::Replace { path, value } => {
+            let target = json
+                .pointer_mut(path)
+                .ok_or_else(|| FixError::PathNotFound(path.clone()))?;
+            *target = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            let value = remove(json, from)?;
+            add(json, path, value)
+        }
+    }
+}
+
+/// Insert a value: into an object by key, or into an array by index ("-" appends)
+fn add(json: &mut Value, path: &str, value: Value) -> Result<(), FixError> {
+    if path.is_empty() {
+        *json = value;
+        return Ok(());
+    }
+    let (parent, key) = split_pointer(path)?;
+    match json.pointer_mut(parent) {
+        Some(Value::Object(obj)) => {
+            obj.insert(key, value);
+            Ok(())
+        }
+        Some(Value::Array(arr)) => {
+            let index = if key == "-" {
+                arr.len()
+            } else {
+                parse_index(&key, arr.len() + 1, path)?
+            };
+            arr.insert(index, value);
+            Ok(())
+        }
+        _ => Err(FixError::PathNotFound(path.to_string())),
+    }
+}
+
+fn remove(json: &mut Value, path: &str) -> Result<Value, FixError> {
+    let (parent, key) = split_pointer(path)?;
+    match json.pointer_mut(parent) {
+        Some(Value::Object(obj)) => obj
+            .remove(&key)
+            .ok_or_else(|| FixError::PathNotFound(path.to_string())),
+        Some(Value::Array(arr)) => {
+            let index = parse_index(&key, arr.len(), path)?;
+            Ok(arr.remove(index))
+        }
+        _ => Err(FixError::PathNotFound(path.to_string())),
+    }
+}
+
+/// Split a JSON Pointer into its parent pointer and unescaped last token
+fn split_pointer(path: &str) -> Result<(&str, String), FixError> {
+    let (parent, last) = path
+        .rsplit_once('/')
+        .ok_or_else(|| FixError::InvalidPointer(path.to_string()))?;
+    Ok((parent, last.replace("~1", "/").replace("~0", "~")))
+}
+
+fn parse_index(token: &str, len: usize, path: &str) -> Result<usize, FixError> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|i| *i < len)
+        .ok_or_else(|| FixError::PathNotFound(path.to_string()))
+}
+
+/// Convert a validation path (e.g. `$.components[0]["a b"]`) to a JSON Pointer
+pub fn pointer_from_path(path: &str) -> Option<String> {
+    let mut rest = path.strip_prefix('$')?;
+    let mut pointer = String::new();
+
+    while !rest.is_empty() {
+        let token = if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            rest = &after[end..];
+            after[..end].to_string()
+        } else if rest.starts_with("[\"") {
+            // Quoted keys are JSON string literals
+            let quoted = &rest[1..];
+            let end = quoted_end(quoted)?;
+            let key: String = serde_json::from_str(&quoted[..end]).ok()?;
+            rest = quoted[end..].strip_prefix(']')?;
+            key
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            rest = &after[end + 1..];
+            after[..end].to_string()
+        } else {
+            return None;
+        };
+        pointer.push('/');
+        pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+    }
+
+    Some(pointer)
+}
+
+/// Length of the JSON string literal at the start of `text`, including quotes
+fn quoted_end(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FixError {
+    #[error("invalid JSON pointer '{0}'")]
+    InvalidPointer(String),
+
+    #[error("path '{0}' not found")]
+    PathNotFound(String),
+}
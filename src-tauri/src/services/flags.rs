@@ -0,0 +1,570 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::validator::ITEM_TYPES;
+
+/// Groups of entity types that share a flag namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagCategory {
+    Item,
+    Terrain,
+    Monster,
+    VehiclePart,
+}
+
+impl FlagCategory {
+    /// The flag namespace used by an entity type, if it has one
+    pub fn for_type(entity_type: &str) -> Option<Self> {
+        match entity_type {
+            t if ITEM_TYPES.contains(&t) => Some(FlagCategory::Item),
+            "terrain" | "furniture" => Some(FlagCategory::Terrain),
+            "MONSTER" => Some(FlagCategory::Monster),
+            "vehicle_part" => Some(FlagCategory::VehiclePart),
+            _ => None,
+        }
+    }
+
+    /// Flags hard-coded in the game engine for this category
+    pub fn engine_flags(self) -> &'static [&'static str] {
+        match self {
+            FlagCategory::Item => ITEM_FLAGS,
+            FlagCategory::Terrain => TERRAIN_FLAGS,
+            FlagCategory::Monster => MONSTER_FLAGS,
+            FlagCategory::VehiclePart => VEHICLE_PART_FLAGS,
+        }
+    }
+}
+
+/// Where a known flag is defined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagSource {
+    /// Hard-coded in the game engine
+    Engine,
+    /// Declared by a `json_flag` entity
+    Json,
+}
+
+/// A flag known to the registry
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlagInfo {
+    pub id: String,
+    pub source: FlagSource,
+    /// Category the flag was listed for
+    pub category: FlagCategory,
+    /// Description from the json_flag `info` field
+    pub info: Option<String>,
+}
+
+/// Known flags: engine flags per category plus `json_flag` entities from loaded packs
+#[derive(Debug, Default)]
+pub struct FlagRegistry {
+    /// json_flag entities by ID, with their `info` text
+    json_flags: BTreeMap<String, Option<String>>,
+}
+
+impl FlagRegistry {
+    /// Build a registry from `json_flag` entity JSON
+    pub fn new<'a>(json_flags: impl IntoIterator<Item = &'a Value>) -> Self {
+        let json_flags = json_flags
+            .into_iter()
+            .filter_map(|json| {
+                let id = json.get("id")?.as_str()?.to_string();
+                let info = json
+                    .get("info")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+                Some((id, info))
+            })
+            .collect();
+
+        Self { json_flags }
+    }
+
+    /// Whether non-monster flags can be checked; most of them are only
+    /// declared in the base game's json_flag entities
+    pub fn has_json_flags(&self) -> bool {
+        !self.json_flags.is_empty()
+    }
+
+    /// Whether a flag is known for a category
+    pub fn is_known(&self, category: FlagCategory, flag: &str) -> bool {
+        category.engine_flags().contains(&flag) || self.json_flags.contains_key(flag)
+    }
+
+    /// All flags offered for a category, sorted by ID
+    pub fn flags_for(&self, category: FlagCategory) -> Vec<FlagInfo> {
+        let mut flags: BTreeMap<&str, FlagInfo> = category
+            .engine_flags()
+            .iter()
+            .map(|id| {
+                (
+                    *id,
+                    FlagInfo {
+                        id: id.to_string(),
+                        source: FlagSource::Engine,
+                        category,
+                        info: None,
+                    },
+                )
+            })
+            .collect();
+
+        for (id, info) in &self.json_flags {
+            flags.insert(
+                id,
+                FlagInfo {
+                    id: id.clone(),
+                    source: FlagSource::Json,
+                    category,
+                    info: info.clone(),
+                },
+            );
+        }
+
+        flags.into_values().collect()
+    }
+
+    /// Flag IDs to suggest from when a flag isn't recognized
+    pub fn candidates(&self, category: FlagCategory) -> impl Iterator<Item = &str> {
+        let json = self.json_flags.keys().map(|id| id.as_str());
+        category.engine_flags().iter().copied().chain(json)
+    }
+}
+
+/// Item flags checked by the engine that aren't declared as json_flag in the base game
+const ITEM_FLAGS: &[&str] = &[
+    "ALLOWS_NATURAL_ATTACKS",
+    "ALLOWS_REMOTE_USE",
+    "BIONIC_TOGGLED",
+    "BIONIC_WEAPON",
+    "CASING",
+    "CORPSE",
+    "FIELD_DRESS",
+    "FIELD_DRESS_FAILED",
+    "IRREMOVABLE",
+    "MISSION_ITEM",
+    "NO_DROP",
+    "NO_UNLOAD",
+    "NO_UNWIELD",
+    "PSEUDO",
+    "QUARTERED",
+    "REDUCED_BASHING",
+    "REDUCED_WEIGHT",
+    "SKINNED",
+    "SPAWN_ACTIVE",
+    "USES_BIONIC_POWER",
+    "USE_UPS",
+    "ZERO_WEIGHT",
+];
+
+/// Terrain and furniture flags checked by the engine
+const TERRAIN_FLAGS: &[&str] = &[
+    "ALARMED",
+    "ALIGN_WORKBENCH",
+    "ALLOW_FIELD_EFFECT",
+    "AUTO_WALL_SYMBOL",
+    "BARRICADABLE_DOOR",
+    "BARRICADABLE_DOOR_DAMAGED",
+    "BARRICADABLE_DOOR_REINFORCED",
+    "BARRICADABLE_DOOR_REINFORCED_DAMAGED",
+    "BARRICADABLE_WINDOW",
+    "BARRICADABLE_WINDOW_CURTAINS",
+    "BLOCK_WIND",
+    "BLOCKSDOOR",
+    "BURROWABLE",
+    "BUTCHER_EQ",
+    "CAN_SIT",
+    "CHIP",
+    "CLIMBABLE",
+    "CLIMB_SIMPLE",
+    "COLLAPSES",
+    "CONNECT_TO_WALL",
+    "CONSOLE",
+    "CONTAINER",
+    "CURRENT",
+    "DECONSTRUCT",
+    "DEEP_WATER",
+    "DESTROY_ITEM",
+    "DIFFICULT_Z",
+    "DIGGABLE",
+    "DIGGABLE_CAN_DEEPEN",
+    "DOOR",
+    "EASY_DECONSTRUCT",
+    "ELEVATOR",
+    "EMITTER",
+    "EXPLODES",
+    "FIRE_CONTAINER",
+    "FLAMMABLE",
+    "FLAMMABLE_ASH",
+    "FLAMMABLE_HARD",
+    "FLAT",
+    "FLAT_SURF",
+    "FLOWER",
+    "FUNGUS",
+    "GOES_DOWN",
+    "GOES_UP",
+    "GROWTH_HARVEST",
+    "GROWTH_MATURE",
+    "GROWTH_SEEDLING",
+    "HARVESTED",
+    "HIDE_PLACE",
+    "INDOORS",
+    "LIQUID",
+    "LIQUIDCONT",
+    "LOCKED",
+    "MINEABLE",
+    "MOUNTABLE",
+    "NOCOLLIDE",
+    "NOITEM",
+    "NO_FLOOR",
+    "NO_SCENT",
+    "NO_SELF_CONNECT",
+    "NO_SIGHT",
+    "OPENCLOSE_INSIDE",
+    "ORGANIC",
+    "PAINFUL",
+    "PERMEABLE",
+    "PICKABLE",
+    "PLACE_ITEM",
+    "PLANT",
+    "PLANTABLE",
+    "PLOWABLE",
+    "RAIL",
+    "RAMP",
+    "RAMP_DOWN",
+    "RAMP_END",
+    "RAMP_UP",
+    "REDUCE_SCENT",
+    "ROAD",
+    "ROUGH",
+    "RUG",
+    "SALT_WATER",
+    "SEALED",
+    "SEEN_FROM_ABOVE",
+    "SHALLOW_WATER",
+    "SHARP",
+    "SHORT",
+    "SHRUB",
+    "SMALL_PASSAGE",
+    "SUN_ROOF_ABOVE",
+    "SUPPORTS_ROOF",
+    "SUPPRESS_SMOKE",
+    "SWIMMABLE",
+    "THIN_OBSTACLE",
+    "TINY",
+    "TRANSLOCATOR",
+    "TRANSPARENT",
+    "TREE",
+    "UNSTABLE",
+    "WALL",
+    "WATER_CUBE",
+    "WINDOW",
+    "WORKOUT_ARMS",
+    "WORKOUT_LEGS",
+    "YOUNG",
+    "Z_TRANSPARENT",
+];
+
+/// Monster flags checked by the engine
+const MONSTER_FLAGS: &[&str] = &[
+    "ABSORBS",
+    "ABSORBS_SPLITS",
+    "ACIDPROOF",
+    "ACIDTRAIL",
+    "ACID_BLOOD",
+    "ANIMAL",
+    "AQUATIC",
+    "ARTHROPOD_BLOOD",
+    "ATTACKMON",
+    "AVOID_DANGER_1",
+    "AVOID_DANGER_2",
+    "AVOID_FALL",
+    "AVOID_FIRE",
+    "BADVENOM",
+    "BASHES",
+    "BILE_BLOOD",
+    "BIRDFOOD",
+    "BLEED",
+    "BONES",
+    "BORES",
+    "CAMOUFLAGE",
+    "CANPLAY",
+    "CAN_BE_ORDERED",
+    "CAN_DIG",
+    "CAN_OPEN_DOORS",
+    "CATFOOD",
+    "CATTLEFODDER",
+    "CBM_CIV",
+    "CBM_OP",
+    "CBM_POWER",
+    "CBM_SCI",
+    "CBM_SUBS",
+    "CBM_TECH",
+    "CHITIN",
+    "CLIMBS",
+    "COLDPROOF",
+    "CONSOLE_DESPAWN",
+    "CONVERSATION",
+    "CURRENT",
+    "DESTROYS",
+    "DIGS",
+    "DOGFOOD",
+    "DRIPS_GASOLINE",
+    "DRIPS_NAPALM",
+    "DROPS_AMMO",
+    "ELECTRIC",
+    "ELECTRIC_FIELD",
+    "ELECTRONIC",
+    "FAT",
+    "FEATHER",
+    "FILTHY",
+    "FIREPROOF",
+    "FIREY",
+    "FISHABLE",
+    "FLAMMABLE",
+    "FLIES",
+    "FUR",
+    "GOODHEARING",
+    "GRABS",
+    "GROUP_BASH",
+    "GROUP_MORALE",
+    "GUILT",
+    "HARDTOSHOOT",
+    "HEARS",
+    "HIT_AND_RUN",
+    "HUMAN",
+    "ID_CARD_DESPAWN",
+    "IMMOBILE",
+    "INSECTICIDEPROOF",
+    "INTERIOR_AMMO",
+    "KEENNOSE",
+    "LARVA",
+    "LEATHER",
+    "LOUDMOVES",
+    "MECH_DEFENSIVE",
+    "MECH_RECON_VISION",
+    "MILITARY_MECH",
+    "MILKABLE",
+    "NIGHT_INVISIBILITY",
+    "NOGIB",
+    "NOHEAD",
+    "NO_BREATHE",
+    "NO_BREED",
+    "NO_FUNG_DMG",
+    "NO_NECRO",
+    "NULL",
+    "PACIFIST",
+    "PARALYZE",
+    "PATH_AVOID_DANGER_1",
+    "PATH_AVOID_DANGER_2",
+    "PATH_AVOID_FALL",
+    "PATH_AVOID_FIRE",
+    "PAY_BOT",
+    "PET_HARNESSABLE",
+    "PET_MOUNTABLE",
+    "PET_WONT_FOLLOW",
+    "PHOTOPHOBIC",
+    "PLASTIC",
+    "POISON",
+    "PRIORITIZE_TARGETS",
+    "PUSH_MON",
+    "PUSH_VEH",
+    "QUEEN",
+    "RANGED_ATTACKER",
+    "REVIVES",
+    "REVIVES_HEALTHY",
+    "RIDEABLE_MECH",
+    "SEES",
+    "SHEARABLE",
+    "SHORTACIDTRAIL",
+    "SLUDGEPROOF",
+    "SLUDGETRAIL",
+    "SMALLSLUDGETRAIL",
+    "SMELLS",
+    "STUMBLES",
+    "STUN_IMMUNE",
+    "SUNDEATH",
+    "SWARMS",
+    "SWIMS",
+    "VENOM",
+    "VERMIN",
+    "WARM",
+    "WATER_CAMOUFLAGE",
+    "WEBWALK",
+    "WOOL",
+];
+
+/// Vehicle part flags checked by the engine
+const VEHICLE_PART_FLAGS: &[&str] = &[
+    "ADVANCED_PLANTER",
+    "AISLE",
+    "AISLE_LIGHT",
+    "ALTERNATOR",
+    "ANCHOR",
+    "ANIMAL_CTRL",
+    "ARMOR",
+    "ATOMIC_LIGHT",
+    "AUTOCLAVE",
+    "BATTERY_MOUNT",
+    "BED",
+    "BEEPER",
+    "BELTABLE",
+    "BIKE_RACK_VEH",
+    "BOARDABLE",
+    "CAMERA",
+    "CAMERA_CONTROL",
+    "CAPTURE_MONSTER_VEH",
+    "CARGO",
+    "CARGO_LOCKING",
+    "CHIMES",
+    "CIRCLE_LIGHT",
+    "CONE_LIGHT",
+    "CONTROLS",
+    "CONTROL_ANIMAL",
+    "COOLER",
+    "COVERED",
+    "CTRL_ELECTRONIC",
+    "CURTAIN",
+    "DISHWASHER",
+    "DOME_LIGHT",
+    "DOOR_MOTOR",
+    "ENABLED_DRAINS_EPOWER",
+    "ENGINE",
+    "EVENTURN",
+    "EXTENDS_VISION",
+    "EXTRA_DRAG",
+    "E_ALTERNATOR",
+    "E_COLD_START",
+    "E_COMBUSTION",
+    "E_DIESEL_FUEL",
+    "E_HEATER",
+    "E_HIGHER_SKILL",
+    "E_STARTS_INSTANTLY",
+    "FAUCET",
+    "FLAT_SURF",
+    "FLOATS",
+    "FOLDABLE",
+    "FREEZER",
+    "FRIDGE",
+    "FUNNEL",
+    "HALF_CIRCLE_LIGHT",
+    "HANDHELD_BATTERY_MOUNT",
+    "HORN",
+    "INITIAL_PART",
+    "INTERNAL",
+    "LIGHT",
+    "LOCKABLE_CARGO",
+    "MUFFLER",
+    "MOUNTABLE",
+    "MULTISQUARE",
+    "MUSCLE_ARMS",
+    "MUSCLE_LEGS",
+    "NAILABLE",
+    "NEEDS_BATTERY_MOUNT",
+    "NEEDS_WHEEL_MOUNT_HEAVY",
+    "NEEDS_WHEEL_MOUNT_LIGHT",
+    "NEEDS_WHEEL_MOUNT_MEDIUM",
+    "NEEDS_WINDOW",
+    "NOINSTALL",
+    "NO_JACK",
+    "NO_REPAIR",
+    "NO_UNINSTALL",
+    "OBSTACLE",
+    "ODDTURN",
+    "ON_CONTROLS",
+    "ON_ROOF",
+    "OPAQUE",
+    "OPENABLE",
+    "OPENCLOSE_INSIDE",
+    "OVER",
+    "PERPETUAL",
+    "PLANTER",
+    "PLOW",
+    "POWER_TRANSFER",
+    "PROTRUSION",
+    "RAIL",
+    "REACTOR",
+    "REAPER",
+    "RECHARGE",
+    "REMOTE_CONTROLS",
+    "REVERSIBLE",
+    "ROOF",
+    "ROTOR",
+    "SCOOP",
+    "SEAT",
+    "SEATBELT",
+    "SECURITY",
+    "SHARP",
+    "SIMPLE_PART",
+    "SMASH_REMOVE",
+    "SOLAR_PANEL",
+    "SPACE_HEATER",
+    "STABLE",
+    "STEERABLE",
+    "STEREO",
+    "TOOL_NONE",
+    "TOOL_SCREWDRIVER",
+    "TOOL_WRENCH",
+    "TRACK",
+    "TRACKED",
+    "TRANSFORM_TERRAIN",
+    "TURRET",
+    "TURRET_CONTROLS",
+    "TURRET_MOUNT",
+    "UNMOUNT_ON_DAMAGE",
+    "UNMOUNT_ON_MOVE",
+    "VARIABLE_SIZE",
+    "VISION",
+    "WASHING_MACHINE",
+    "WATER_PURIFIER",
+    "WATER_WHEEL",
+    "WHEEL",
+    "WHEEL_MOUNT_HEAVY",
+    "WHEEL_MOUNT_LIGHT",
+    "WHEEL_MOUNT_MEDIUM",
+    "WIDE_CONE_LIGHT",
+    "WINDOW",
+    "WIND_POWERED",
+    "WIND_TURBINE",
+    "WIRING",
+    "WORKBENCH",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn json_flags_apply_to_every_category() {
+        let declared = json!({ "type": "json_flag", "id": "CUSTOM_FLAG", "info": "Custom" });
+        let registry = FlagRegistry::new([&declared]);
+
+        for category in [
+            FlagCategory::Item,
+            FlagCategory::Terrain,
+            FlagCategory::Monster,
+            FlagCategory::VehiclePart,
+        ] {
+            assert!(registry.is_known(category, "CUSTOM_FLAG"));
+            let offered = registry
+                .flags_for(category)
+                .into_iter()
+                .find(|flag| flag.id == "CUSTOM_FLAG")
+                .unwrap();
+            assert_eq!(offered.source, FlagSource::Json);
+            assert_eq!(offered.info.as_deref(), Some("Custom"));
+            assert!(registry.candidates(category).any(|id| id == "CUSTOM_FLAG"));
+        }
+    }
+
+    #[test]
+    fn vehicle_parts_can_be_mountable() {
+        let registry = FlagRegistry::default();
+        assert!(registry.is_known(FlagCategory::VehiclePart, "MOUNTABLE"));
+    }
+}
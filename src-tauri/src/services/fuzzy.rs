@@ -0,0 +1,140 @@
+/// Levenshtein edit distance between two strings, ignoring ASCII case
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let b: Vec<char> = b.chars().map(|c| c.to_ascii_lowercase()).collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// The candidate closest to `target`, if any is close enough to be a likely typo
+pub fn closest_match<'a>(
+    target: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = (target.chars().count() / 3).max(2);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(target, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by(|(da, a), (db, b)| da.cmp(db).then_with(|| a.cmp(b)))
+        .map(|(_, candidate)| candidate)
+}
+
+/// Bonus for a query word character that follows the previous match directly
+const CONSECUTIVE_BONUS: f32 = 2.0;
+
+/// Bonus for a match at the start of a word ("brute" in "mon_zombie_brute")
+const WORD_START_BONUS: f32 = 3.0;
+
+/// Cost of each skipped character between matches
+const GAP_PENALTY: f32 = 0.1;
+
+/// Bonus when a field is exactly the query, or starts with it
+const EXACT_BONUS: f32 = 20.0;
+const PREFIX_BONUS: f32 = 8.0;
+
+/// Score of a query word that only matches with a typo, per character
+const TYPO_SCORE: f32 = 0.5;
+
+/// How well a quick-open style query matches an entity's fields (ID, name), if at all.
+/// Higher is better.
+///
+/// Every word of the query has to match one of the fields, either as a
+/// subsequence of its characters ("zmbbrt" matches "zombie_brute") or, for
+/// words of four or more characters, within one typo of a word in the field.
+pub fn fuzzy_score(query: &str, fields: &[&str]) -> Option<f32> {
+    let query = query.trim().to_lowercase();
+    let fields: Vec<String> = fields.iter().map(|f| f.to_lowercase()).collect();
+
+    let mut score = 0.0;
+    for word in query.split_whitespace() {
+        score += fields
+            .iter()
+            .filter_map(|field| word_score(word, field))
+            .max_by(f32::total_cmp)?;
+    }
+
+    let bonus = fields
+        .iter()
+        .map(|field| {
+            if *field == query {
+                EXACT_BONUS
+            } else if field.starts_with(&query) {
+                PREFIX_BONUS
+            } else {
+                0.0
+            }
+        })
+        .fold(0.0, f32::max);
+
+    Some(score + bonus)
+}
+
+/// Best subsequence score of one query word in a field, falling back to a typo match
+fn word_score(word: &str, field: &str) -> Option<f32> {
+    let word: Vec<char> = word.chars().collect();
+    let field: Vec<char> = field.chars().collect();
+    let first = *word.first()?;
+
+    // Try each place the first character occurs, matching greedily from there
+    let subsequence = (0..field.len())
+        .filter(|&start| field[start] == first)
+        .filter_map(|start| subsequence_score(&word, &field, start))
+        .max_by(f32::total_cmp);
+
+    subsequence.or_else(|| typo_score(&word, &field))
+}
+
+fn subsequence_score(word: &[char], field: &[char], start: usize) -> Option<f32> {
+    let mut score = 0.0;
+    let mut previous: Option<usize> = None;
+    let mut pos = start;
+
+    for &c in word {
+        let found = pos + field[pos..].iter().position(|&f| f == c)?;
+        score += 1.0;
+        match previous {
+            Some(p) if found == p + 1 => score += CONSECUTIVE_BONUS,
+            Some(p) => score -= GAP_PENALTY * (found - p - 1) as f32,
+            None => {}
+        }
+        if found == 0 || !field[found - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        previous = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score)
+}
+
+/// A word of four or more characters within one edit of a field word (or its
+/// start, for words still being typed)
+fn typo_score(word: &[char], field: &[char]) -> Option<f32> {
+    if word.len() < 4 {
+        return None;
+    }
+    let word: String = word.iter().collect();
+    let close = field
+        .split(|c| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .any(|part| {
+            let whole: String = part.iter().collect();
+            let start: String = part.iter().take(word.chars().count()).collect();
+            edit_distance(&word, &whole) <= 1 || edit_distance(&word, &start) <= 1
+        });
+    close.then(|| word.chars().count() as f32 * TYPO_SCORE)
+}
@@ -0,0 +1,308 @@
+use serde_json::Value;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use super::diff::find_entity;
+use crate::models::{CommitInfo, Entity, EntityMeta, FileStatus};
+
+/// Commits searched when looking for the last change to an entity
+const MAX_BLAME_COMMITS: usize = 50;
+
+/// Separates fields in `git log` output
+const FIELD_SEPARATOR: char = '\x1f';
+
+/// Config passed to every command. Packs come from anywhere, and a
+/// repository's own config can name programs for git to run (an fsmonitor
+/// hook during `status`, a signature checker during `log`, ...). These
+/// settings turn off the ones that can be overridden by name; filter
+/// drivers can't be, so repositories that define their own are refused
+/// when opened (see [`Repository::open`]).
+const SAFE_CONFIG: &[&str] = &[
+    "core.fsmonitor=false",
+    "core.pager=cat",
+    "core.sshCommand=",
+    "diff.external=",
+    "log.showSignature=false",
+    "protocol.allow=never",
+];
+
+/// The git work tree a pack lives in, read through the local `git` executable.
+/// Every command works on the local repository only; nothing is fetched.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    /// Pack directory, used as the working directory of every command
+    pack_path: PathBuf,
+    /// Pack directory relative to the work tree root ("" or ending in '/')
+    prefix: String,
+}
+
+impl Repository {
+    /// The repository containing a pack, or None if the pack isn't in a git work tree.
+    ///
+    /// `status` runs the clean filter of any file whose contents it has to
+    /// compare, and the filter is a command from the repository's config.
+    /// A repository whose own config defines filter drivers is refused;
+    /// drivers from the user's global or system config are trusted.
+    pub fn open(pack_path: &Path) -> Result<Option<Self>, GitError> {
+        let output = match git(pack_path, &["rev-parse", "--show-prefix"]) {
+            Ok(output) => output,
+            Err(GitError::CommandFailed { .. }) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let repository = Self {
+            pack_path: pack_path.to_path_buf(),
+            prefix: output.trim_end_matches('\n').to_string(),
+        };
+
+        let drivers = repository.filter_drivers()?;
+        if !drivers.is_empty() {
+            return Err(GitError::FilterDrivers {
+                path: pack_path.to_path_buf(),
+                drivers,
+            });
+        }
+        Ok(Some(repository))
+    }
+
+    /// Filter driver commands defined in the repository's own config
+    /// (including files it includes), e.g. "filter.lfs.clean"
+    fn filter_drivers(&self) -> Result<Vec<String>, GitError> {
+        let output = match self.run(&[
+            "config",
+            "--show-scope",
+            "--get-regexp",
+            r"^filter\..*\.(clean|smudge|process)$",
+        ]) {
+            Ok(output) => output,
+            // Exit status 1 without a message means nothing matched
+            Err(GitError::CommandFailed { message, .. }) if message.is_empty() => {
+                return Ok(Vec::new())
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let (scope, entry) = line.split_once('\t')?;
+                let key = entry.split(' ').next().unwrap_or(entry);
+                matches!(scope, "local" | "worktree").then(|| key.to_string())
+            })
+            .collect())
+    }
+
+    /// Current branch, or None when HEAD is detached
+    pub fn branch(&self) -> Result<Option<String>, GitError> {
+        match self.run(&["symbolic-ref", "--short", "-q", "HEAD"]) {
+            Ok(output) => Ok(Some(output.trim().to_string())),
+            Err(GitError::CommandFailed { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Files in the pack that are staged, modified or untracked.
+    /// `unsaved` is always false; the caller merges in its own dirty files.
+    pub fn status(&self) -> Result<Vec<FileStatus>, GitError> {
+        let output = self.run(&[
+            "status",
+            "--porcelain=v1",
+            "-z",
+            "--untracked-files=all",
+            // Submodules are repositories of their own, with config of their own
+            "--ignore-submodules=all",
+            "--",
+            ".",
+        ])?;
+
+        let mut files = Vec::new();
+        let mut entries = output.split('\0');
+        while let Some(entry) = entries.next() {
+            if entry.len() < 4 {
+                continue;
+            }
+            let (code, path) = entry.split_at(3);
+            let mut code = code.chars();
+            let (index, work_tree) = (code.next().unwrap_or(' '), code.next().unwrap_or(' '));
+            if matches!(index, 'R' | 'C') {
+                // The original path of a rename or copy follows as its own entry
+                entries.next();
+            }
+
+            // Paths are relative to the work tree root
+            let Some(path) = path.strip_prefix(self.prefix.as_str()) else {
+                continue;
+            };
+            let untracked = index == '?';
+            files.push(FileStatus {
+                path: PathBuf::from(path),
+                staged: !untracked && index != ' ',
+                modified: !untracked && work_tree != ' ',
+                untracked,
+                unsaved: false,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Contents of a pack file at a revision, or None if the file (or the
+    /// revision, e.g. HEAD before the first commit) doesn't exist
+    pub fn file_at(&self, revision: &str, file: &Path) -> Result<Option<String>, GitError> {
+        let object = format!("{}:{}", revision, pathspec(file));
+        if self.run(&["cat-file", "-e", &object]).is_err() {
+            return Ok(None);
+        }
+        // `cat-file` prints the blob as stored, without textconv drivers
+        self.run(&["cat-file", "blob", &object]).map(Some)
+    }
+
+    /// An entity as it is in a pack file at a revision
+    pub fn entity_at(&self, revision: &str, entity: &Entity) -> Result<Option<Value>, GitError> {
+        let Some(content) = self.file_at(revision, &entity.source_file)? else {
+            return Ok(None);
+        };
+        let json: Value = serde_json::from_str(&content).map_err(|e| GitError::ParseError {
+            path: entity.source_file.clone(),
+            revision: revision.to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(json.as_array().and_then(|array| find_entity(array, entity)))
+    }
+
+    /// The most recent commit that changed an entity's definition.
+    /// Only commits touching its source file are read, newest first, and an
+    /// entity is matched by type and ID since its position may have moved.
+    pub fn last_change(&self, entity: &Entity) -> Result<Option<CommitInfo>, GitError> {
+        let commits = self.log(&entity.source_file, MAX_BLAME_COMMITS)?;
+        let key = entity.key();
+
+        let mut newer: Option<(CommitInfo, Option<Value>)> = None;
+        for (i, commit) in commits.iter().enumerate() {
+            let version = self
+                .file_at(&commit.hash, &entity.source_file)?
+                .and_then(|content| find_by_key(&content, &key));
+            match newer {
+                // Not in the newest commit, so not in HEAD either
+                None if version.is_none() => return Ok(None),
+                Some((newer_commit, newer_version)) if newer_version != version => {
+                    return Ok(Some(newer_commit));
+                }
+                _ => {}
+            }
+            // The oldest commit in the file's history added it
+            if i + 1 == commits.len() && commits.len() < MAX_BLAME_COMMITS {
+                return Ok(Some(commit.clone()));
+            }
+            newer = Some((commit.clone(), version));
+        }
+        Ok(None)
+    }
+
+    /// Commits that touched a pack file, newest first
+    fn log(&self, file: &Path, limit: usize) -> Result<Vec<CommitInfo>, GitError> {
+        let output = self.run(&[
+            "log",
+            &format!("-n{}", limit),
+            "--format=%H%x1f%an%x1f%aI%x1f%s",
+            "--",
+            &pathspec(file),
+        ])?;
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, FIELD_SEPARATOR);
+                Some(CommitInfo {
+                    hash: fields.next()?.to_string(),
+                    author: fields.next()?.to_string(),
+                    date: fields.next()?.to_string(),
+                    summary: fields.next().unwrap_or_default().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    fn run(&self, args: &[&str]) -> Result<String, GitError> {
+        git(&self.pack_path, args)
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let mut command = Command::new("git");
+    for config in SAFE_CONFIG {
+        command.arg("-c").arg(config);
+    }
+    let output = command
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        // Reading status shouldn't take the index lock from other git tools
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => GitError::NotInstalled,
+            _ => GitError::CommandFailed {
+                command: args.join(" "),
+                message: e.to_string(),
+            },
+        })?;
+
+    if !output.status.success() {
+        return Err(GitError::CommandFailed {
+            command: args.join(" "),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A pack-relative path as a pathspec relative to the working directory,
+/// with '/' separators on every platform
+fn pathspec(file: &Path) -> String {
+    let parts: Vec<String> = file
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    format!("./{}", parts.join("/"))
+}
+
+/// The entity with a key in a version of a file, or None if it isn't there
+/// or the file didn't parse at that point in history
+fn find_by_key(content: &str, key: &str) -> Option<Value> {
+    let json: Value = serde_json::from_str(content).ok()?;
+    json.as_array()?
+        .iter()
+        .find(|value| {
+            EntityMeta::from_json(value)
+                .is_some_and(|meta| format!("{}:{}", meta.entity_type, meta.id) == key)
+        })
+        .cloned()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GitError {
+    #[error("git is not installed or not on the PATH")]
+    NotInstalled,
+
+    #[error("{0} is not inside a git repository")]
+    NotARepository(PathBuf),
+
+    #[error(
+        "The repository of {path} defines filter drivers ({}), which git would run; it isn't read",
+        drivers.join(", ")
+    )]
+    FilterDrivers { path: PathBuf, drivers: Vec<String> },
+
+    #[error("git {command} failed: {message}")]
+    CommandFailed { command: String, message: String },
+
+    #[error("Parse error in {path} at {revision}: {message}")]
+    ParseError {
+        path: PathBuf,
+        revision: String,
+        message: String,
+    },
+}
@@ -0,0 +1,299 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::models::{ContentPack, Entity, EntityKey, EntityMeta};
+
+/// Transactions kept per pack; the oldest are dropped first
+const MAX_HISTORY: usize = 100;
+
+/// One entity's state before and after a transaction (None when absent)
+#[derive(Debug, Clone)]
+struct EntityChange {
+    key: EntityKey,
+    before: Option<Entity>,
+    after: Option<Entity>,
+}
+
+/// Everything one command changed in a pack, undone and redone as a unit
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    label: String,
+    changes: Vec<EntityChange>,
+    dirty_files_before: Vec<PathBuf>,
+    dirty_files_after: Vec<PathBuf>,
+}
+
+/// Records the state of every entity a command touches in one pack, so the
+/// command can be turned into a [`Transaction`] once it's done.
+/// Commands make their changes through the journal instead of the pack.
+#[derive(Debug)]
+pub struct Journal {
+    label: String,
+    before: Vec<(EntityKey, Option<Entity>)>,
+    dirty_files: Option<Vec<PathBuf>>,
+}
+
+impl Journal {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            before: Vec::new(),
+            dirty_files: None,
+        }
+    }
+
+    fn touch(&mut self, pack: &ContentPack, key: &str) {
+        if self.dirty_files.is_none() {
+            self.dirty_files = Some(pack.dirty_files.clone());
+        }
+        if !self.before.iter().any(|(k, _)| k == key) {
+            self.before
+                .push((key.to_string(), pack.entities.get(key).cloned()));
+        }
+    }
+
+    /// [`ContentPack::set_entity_json`], recorded
+    pub fn set_entity_json(
+        &mut self,
+        pack: &mut ContentPack,
+        key: &str,
+        json: Value,
+    ) -> Result<EntityKey, String> {
+        self.touch(pack, key);
+        // The entity moves to a new key if its type or ID changes
+        if let Some(meta) = EntityMeta::from_json(&json) {
+            self.touch(pack, &format!("{}:{}", meta.entity_type, meta.id));
+        }
+        pack.set_entity_json(key, json)
+    }
+
+    /// [`ContentPack::add_entity`], recorded
+    pub fn add_entity(
+        &mut self,
+        pack: &mut ContentPack,
+        json: Value,
+        source_file: PathBuf,
+    ) -> Result<EntityKey, String> {
+        if let Some(meta) = EntityMeta::from_json(&json) {
+            self.touch(pack, &format!("{}:{}", meta.entity_type, meta.id));
+        }
+        pack.add_entity(json, source_file)
+    }
+
+    /// [`ContentPack::move_entity`], recorded
+    pub fn move_entity(
+        &mut self,
+        pack: &mut ContentPack,
+        key: &str,
+        source_file: PathBuf,
+    ) -> Result<(), String> {
+        self.touch(pack, key);
+        pack.move_entity(key, source_file)
+    }
+
+    /// The finished transaction, or None if nothing changed
+    pub fn finish(self, pack: &ContentPack) -> Option<Transaction> {
+        let changes: Vec<EntityChange> = self
+            .before
+            .into_iter()
+            .map(|(key, before)| EntityChange {
+                after: pack.entities.get(&key).cloned(),
+                key,
+                before,
+            })
+            .filter(|change| !same_entity(&change.before, &change.after))
+            .collect();
+
+        if changes.is_empty() {
+            return None;
+        }
+
+        Some(Transaction {
+            label: self.label,
+            changes,
+            dirty_files_before: self.dirty_files.unwrap_or_default(),
+            dirty_files_after: pack.dirty_files.clone(),
+        })
+    }
+}
+
+fn same_entity(a: &Option<Entity>, b: &Option<Entity>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            a.json == b.json
+                && a.dirty == b.dirty
+                && a.source_file == b.source_file
+                && a.array_index == b.array_index
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Undo and redo stacks for one pack
+#[derive(Debug, Default)]
+pub struct PackHistory {
+    undo: Vec<Transaction>,
+    redo: Vec<Transaction>,
+}
+
+/// What undo and redo would do next, for the UI
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryState {
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+}
+
+/// A transaction that was undone or redone
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryStep {
+    pub label: String,
+    /// Keys whose entity changed, appeared or disappeared
+    pub changed_keys: Vec<EntityKey>,
+}
+
+impl PackHistory {
+    /// Add a finished transaction; anything that could be redone is dropped
+    pub fn record(&mut self, transaction: Transaction) {
+        self.redo.clear();
+        self.undo.push(transaction);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn undo(&mut self, pack: &mut ContentPack) -> Option<HistoryStep> {
+        let transaction = self.undo.pop()?;
+        let step = restore(pack, &transaction, Direction::Back);
+        self.redo.push(transaction);
+        Some(step)
+    }
+
+    pub fn redo(&mut self, pack: &mut ContentPack) -> Option<HistoryStep> {
+        let transaction = self.redo.pop()?;
+        let step = restore(pack, &transaction, Direction::Forward);
+        self.undo.push(transaction);
+        Some(step)
+    }
+
+    /// After saving, every recorded state differs from what's on disk,
+    /// so going back or forward to it has to leave the entity dirty.
+    /// Saving also renumbers the entities in each file it writes, so
+    /// recorded entities still in the same file take their new position
+    /// from `pack`.
+    pub fn mark_saved(&mut self, pack: &ContentPack) {
+        for transaction in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            let mut files = Vec::new();
+            for change in &mut transaction.changes {
+                for entity in [&mut change.before, &mut change.after]
+                    .into_iter()
+                    .flatten()
+                {
+                    entity.dirty = true;
+                    if let Some(saved) = pack
+                        .entities
+                        .get(&change.key)
+                        .filter(|saved| saved.source_file == entity.source_file)
+                    {
+                        entity.array_index = saved.array_index;
+                    }
+                    files.push(entity.source_file.clone());
+                }
+            }
+            for file in files {
+                for dirty_files in [
+                    &mut transaction.dirty_files_before,
+                    &mut transaction.dirty_files_after,
+                ] {
+                    if !dirty_files.contains(&file) {
+                        dirty_files.push(file.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn state(&self) -> HistoryState {
+        HistoryState {
+            undo_label: self.undo.last().map(|t| t.label.clone()),
+            redo_label: self.redo.last().map(|t| t.label.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Back,
+    Forward,
+}
+
+/// Put the pack's entities and dirty files in the state before or after a transaction
+fn restore(pack: &mut ContentPack, transaction: &Transaction, direction: Direction) -> HistoryStep {
+    for change in &transaction.changes {
+        let state = match direction {
+            Direction::Back => &change.before,
+            Direction::Forward => &change.after,
+        };
+        match state {
+            Some(entity) => {
+                pack.entities.insert(change.key.clone(), entity.clone());
+            }
+            None => {
+                pack.entities.remove(&change.key);
+            }
+        }
+    }
+
+    pack.dirty_files = match direction {
+        Direction::Back => transaction.dirty_files_before.clone(),
+        Direction::Forward => transaction.dirty_files_after.clone(),
+    };
+
+    HistoryStep {
+        label: transaction.label.clone(),
+        changed_keys: transaction.changes.iter().map(|c| c.key.clone()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn undo_after_save_restores_saved_positions() {
+        let file = PathBuf::from("items.json");
+        let mut pack = ContentPack::new(Uuid::nil(), "test".into(), PathBuf::from("pack"), false);
+        for (index, id) in [(2, "rock"), (5, "stick")] {
+            let json = json!({ "type": "GENERIC", "id": id });
+            let entity = Entity::from_json(json, file.clone(), index).unwrap();
+            pack.entities.insert(entity.key(), entity);
+        }
+
+        let mut journal = Journal::new("Edit stick");
+        let edited = json!({ "type": "GENERIC", "id": "stick", "weight": "1 kg" });
+        journal
+            .set_entity_json(&mut pack, "GENERIC:stick", edited)
+            .unwrap();
+        let mut history = PackHistory::default();
+        history.record(journal.finish(&pack).unwrap());
+
+        // What save_pack does: renumber, clear dirty state
+        for entity in pack.entities.values_mut() {
+            entity.array_index = if entity.meta.id == "rock" { 0 } else { 1 };
+            entity.dirty = false;
+        }
+        pack.dirty_files.clear();
+        history.mark_saved(&pack);
+
+        history.undo(&mut pack).unwrap();
+        let stick = &pack.entities["GENERIC:stick"];
+        assert_eq!(stick.array_index, 1);
+        assert!(stick.dirty);
+        assert!(stick.json.get("weight").is_none());
+    }
+}
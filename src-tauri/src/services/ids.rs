@@ -0,0 +1,177 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::validator::ITEM_TYPES;
+use crate::models::{ContentPack, Entity, EntityKey, PackId, Workspace};
+
+/// Prefix convention used when none is configured
+pub const DEFAULT_PREFIX_PATTERN: &str = "{mod_id}_";
+
+/// Types whose IDs aren't unique by design (several mapgens per terrain)
+const IGNORED_TYPES: &[&str] = &["mapgen"];
+
+/// Types identified by another entity's ID, so they can't follow the prefix convention
+const UNPREFIXED_TYPES: &[&str] = &["recipe", "uncraft", "mapgen"];
+
+/// A naming or collision problem with an entity ID
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdIssue {
+    /// Issue code (e.g., "ID_COLLISION", "UNINTENTIONAL_OVERRIDE", "ID_PREFIX")
+    pub code: String,
+    pub message: String,
+    pub entity_key: EntityKey,
+    pub entity_id: String,
+    pub entity_type: String,
+    /// Pack that defines the same ID, for collisions and overrides
+    pub other_pack_id: Option<PackId>,
+    pub other_pack_name: Option<String>,
+}
+
+/// Check every ID in a pack against the rest of the workspace and the prefix convention.
+///
+/// - `DUPLICATE_ID`: defined more than once in the pack itself
+/// - `UNINTENTIONAL_OVERRIDE`: replaces an entity from the base game or a
+///   dependency without signalling intent (copy-from itself, or living in an
+///   "override" directory/file)
+/// - `ID_COLLISION`: also defined by a pack that isn't a dependency, so
+///   whichever loads last wins
+/// - `ID_PREFIX`: a new ID doesn't start with the configured prefix
+pub fn check_pack_ids(
+    workspace: &Workspace,
+    pack: &ContentPack,
+    prefix_pattern: &str,
+) -> Vec<IdIssue> {
+    let mut others: HashMap<(&str, &str), Vec<&ContentPack>> = HashMap::new();
+    for other in workspace.packs.values().filter(|p| p.id != pack.id) {
+        for entity in other.entities.values() {
+            let packs = others
+                .entry((id_namespace(&entity.meta.entity_type), &entity.meta.id))
+                .or_default();
+            if !packs.iter().any(|p| p.id == other.id) {
+                packs.push(other);
+            }
+        }
+    }
+
+    let mut own: HashMap<(&str, &str), usize> = HashMap::new();
+    for entity in pack.entities.values() {
+        *own.entry((id_namespace(&entity.meta.entity_type), &entity.meta.id))
+            .or_default() += 1;
+    }
+
+    let mod_id = pack.metadata.as_ref().and_then(|m| m.mod_id.as_deref());
+    let prefix = mod_id.map(|mod_id| prefix_pattern.replace("{mod_id}", mod_id));
+
+    let mut entities: Vec<(&EntityKey, &Entity)> = pack.entities.iter().collect();
+    entities.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut issues = Vec::new();
+    for (key, entity) in entities {
+        let entity_type = entity.meta.entity_type.as_str();
+        if IGNORED_TYPES.contains(&entity_type) {
+            continue;
+        }
+        let id = entity.meta.id.as_str();
+        let namespace = id_namespace(entity_type);
+        let issue = |code: &str, message: String, other: Option<&ContentPack>| IdIssue {
+            code: code.to_string(),
+            message,
+            entity_key: key.clone(),
+            entity_id: id.to_string(),
+            entity_type: entity_type.to_string(),
+            other_pack_id: other.map(|p| p.id),
+            other_pack_name: other.map(|p| p.name.clone()),
+        };
+
+        if own.get(&(namespace, id)).is_some_and(|count| *count > 1) {
+            issues.push(issue(
+                "DUPLICATE_ID",
+                format!("'{}' is defined more than once in this pack", id),
+                None,
+            ));
+        }
+
+        let defined_elsewhere = others
+            .get(&(namespace, id))
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        for other in defined_elsewhere {
+            if is_dependency(pack, other) {
+                if !has_override_intent(entity) {
+                    issues.push(issue(
+                        "UNINTENTIONAL_OVERRIDE",
+                        format!(
+                            "'{}' replaces the definition in {}; use copy-from \"{}\" if this is intended",
+                            id, other.name, id
+                        ),
+                        Some(other),
+                    ));
+                }
+            } else {
+                issues.push(issue(
+                    "ID_COLLISION",
+                    format!(
+                        "'{}' is also defined by {}; whichever loads last wins",
+                        id, other.name
+                    ),
+                    Some(other),
+                ));
+            }
+        }
+
+        let overrides = defined_elsewhere
+            .iter()
+            .any(|other| is_dependency(pack, other));
+        if let Some(prefix) = &prefix {
+            if !overrides
+                && !UNPREFIXED_TYPES.contains(&entity_type)
+                && !id.starts_with(prefix.as_str())
+            {
+                issues.push(issue(
+                    "ID_PREFIX",
+                    format!("'{}' should start with '{}'", id, prefix),
+                    None,
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Entity types that share one ID namespace in the game
+pub fn id_namespace(entity_type: &str) -> &str {
+    if ITEM_TYPES.contains(&entity_type) {
+        "item"
+    } else {
+        entity_type
+    }
+}
+
+/// Whether `other` is the base game or one of the pack's dependencies
+fn is_dependency(pack: &ContentPack, other: &ContentPack) -> bool {
+    let Some(other_meta) = &other.metadata else {
+        return false;
+    };
+    // modinfo's `type` is always MOD_INFO; the base game is the "core" category
+    if other_meta.category.as_deref() == Some("core") {
+        return true;
+    }
+    match (&pack.metadata, &other_meta.mod_id) {
+        (Some(meta), Some(other_id)) => meta.dependencies.contains(other_id),
+        _ => false,
+    }
+}
+
+/// Whether an entity is clearly meant to replace an existing definition
+fn has_override_intent(entity: &Entity) -> bool {
+    if entity.meta.copy_from.as_deref() == Some(entity.meta.id.as_str()) {
+        return true;
+    }
+    entity.source_file.components().any(|c| {
+        c.as_os_str()
+            .to_str()
+            .is_some_and(|s| s.to_lowercase().contains("override"))
+    })
+}
@@ -0,0 +1,291 @@
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+use super::ids::id_namespace;
+use super::units::{self, UnitKind, INFERRED_KINDS};
+use crate::models::{PackId, Workspace};
+
+/// Longest copy-from chain followed before giving up (guards against cycles)
+const MAX_DEPTH: usize = 16;
+
+/// Fields that modify the inherited values instead of being copied
+const DIRECTIVES: &[&str] = &["relative", "proportional", "extend", "delete"];
+
+/// Resolves `copy-from` the way the game does, producing an entity's effective JSON
+pub struct InheritanceResolver<'a> {
+    /// Definitions by ID namespace and ID, with the load-order position of their pack
+    definitions: HashMap<(&'a str, &'a str), Vec<(usize, &'a Value)>>,
+    /// Load-order position of each pack
+    positions: HashMap<PackId, usize>,
+    /// Pack at each load-order position
+    packs: Vec<PackId>,
+}
+
+impl<'a> InheritanceResolver<'a> {
+    pub fn from_workspace(workspace: &'a Workspace) -> Self {
+        let mut definitions: HashMap<(&'a str, &'a str), Vec<(usize, &'a Value)>> = HashMap::new();
+        let mut positions = HashMap::new();
+        let mut packs = Vec::new();
+
+        for (position, pack) in workspace
+            .load_order
+            .iter()
+            .filter_map(|id| workspace.packs.get(id))
+            .enumerate()
+        {
+            positions.insert(pack.id, position);
+            packs.push(pack.id);
+            for entity in pack.entities.values() {
+                definitions
+                    .entry((
+                        id_namespace(&entity.meta.entity_type),
+                        entity.meta.id.as_str(),
+                    ))
+                    .or_default()
+                    .push((position, &entity.json));
+            }
+        }
+
+        Self {
+            definitions,
+            positions,
+            packs,
+        }
+    }
+
+    /// Effective JSON of an entity from the given pack, with `copy-from`,
+    /// `relative`, `proportional`, `extend` and `delete` applied.
+    /// Entities whose parent can't be found are returned as they are.
+    pub fn resolve(&self, json: &Value, pack_id: PackId) -> Value {
+        let position = self.positions.get(&pack_id).copied().unwrap_or(usize::MAX);
+        self.resolve_at(json, position, 0)
+    }
+
+    fn resolve_at(&self, json: &Value, position: usize, depth: usize) -> Value {
+        let Some(parent_id) = json.get("copy-from").and_then(|v| v.as_str()) else {
+            return json.clone();
+        };
+        if depth >= MAX_DEPTH {
+            return json.clone();
+        }
+
+        let entity_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        let own_id = own_id(json);
+
+        let Some((parent_position, parent)) =
+            self.parent(entity_type, parent_id, position, own_id == Some(parent_id))
+        else {
+            return json.clone();
+        };
+
+        let parent = self.resolve_at(parent, parent_position, depth + 1);
+        inherit(parent, json)
+    }
+
+    /// The resolved definition an entity builds on, with the pack it comes from:
+    /// its copy-from parent, or the definition it replaces in an earlier pack
+    pub fn base(&self, json: &Value, pack_id: PackId) -> Option<(PackId, Value)> {
+        let position = *self.positions.get(&pack_id)?;
+        let entity_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        let own_id = own_id(json);
+
+        let (parent_position, parent) = match json.get("copy-from").and_then(|v| v.as_str()) {
+            Some(parent_id) => {
+                self.parent(entity_type, parent_id, position, own_id == Some(parent_id))?
+            }
+            None => self.parent(entity_type, own_id?, position, true)?,
+        };
+
+        Some((
+            self.packs[parent_position],
+            self.resolve_at(parent, parent_position, 1),
+        ))
+    }
+
+    /// The definition a copy-from refers to: the latest one loaded at or
+    /// before `position`, or strictly before it when an entity copies from itself
+    fn parent(
+        &self,
+        entity_type: &str,
+        parent_id: &str,
+        position: usize,
+        copies_itself: bool,
+    ) -> Option<(usize, &'a Value)> {
+        self.definitions
+            .get(&(id_namespace(entity_type), parent_id))?
+            .iter()
+            .rev()
+            .find(|(p, _)| {
+                if copies_itself {
+                    *p < position
+                } else {
+                    *p <= position
+                }
+            })
+            .copied()
+    }
+}
+
+fn own_id(json: &Value) -> Option<&str> {
+    json.get("id")
+        .or_else(|| json.get("abstract"))
+        .and_then(|v| v.as_str())
+}
+
+/// Apply a child entity on top of its resolved parent
+fn inherit(parent: Value, child: &Value) -> Value {
+    let (Value::Object(mut result), Some(child)) = (parent, child.as_object()) else {
+        return child.clone();
+    };
+    result.remove("abstract");
+
+    for (key, value) in child {
+        if !DIRECTIVES.contains(&key.as_str()) {
+            result.insert(key.clone(), value.clone());
+        }
+    }
+
+    let entity_type = child
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    if let Some(Value::Object(relative)) = child.get("relative") {
+        apply_arithmetic(&mut result, relative, &entity_type, Arithmetic::Add);
+    }
+    if let Some(Value::Object(proportional)) = child.get("proportional") {
+        apply_arithmetic(
+            &mut result,
+            proportional,
+            &entity_type,
+            Arithmetic::Multiply,
+        );
+    }
+    if let Some(Value::Object(extend)) = child.get("extend") {
+        apply_extend(&mut result, extend);
+    }
+    if let Some(Value::Object(delete)) = child.get("delete") {
+        apply_delete(&mut result, delete);
+    }
+
+    Value::Object(result)
+}
+
+#[derive(Clone, Copy)]
+enum Arithmetic {
+    Add,
+    Multiply,
+}
+
+impl Arithmetic {
+    fn apply(self, value: f64, operand: f64) -> f64 {
+        match self {
+            Arithmetic::Add => value + operand,
+            Arithmetic::Multiply => value * operand,
+        }
+    }
+}
+
+/// `relative` adds to inherited values and `proportional` multiplies them,
+/// recursing into nested objects (e.g. `"relative": { "melee_damage": { "bash": 2 } }`)
+fn apply_arithmetic(
+    target: &mut Map<String, Value>,
+    operands: &Map<String, Value>,
+    entity_type: &str,
+    op: Arithmetic,
+) {
+    for (field, operand) in operands {
+        let current = target.get(field).cloned();
+        let updated = match (current, operand) {
+            (Some(Value::Object(mut inner)), Value::Object(operands)) => {
+                apply_arithmetic(&mut inner, operands, entity_type, op);
+                Some(Value::Object(inner))
+            }
+            (Some(Value::Number(n)), _) => {
+                let Some(operand) = operand.as_f64() else {
+                    continue;
+                };
+                number_like(&n, op.apply(n.as_f64().unwrap_or(0.0), operand))
+            }
+            (Some(Value::String(text)), _) => {
+                let Some(kind) = unit_kind(entity_type, field, &text) else {
+                    continue;
+                };
+                let Ok(base) = units::parse(&text, kind) else {
+                    continue;
+                };
+                let operand = match (op, operand) {
+                    (Arithmetic::Add, Value::String(s)) => {
+                        units::parse(s, kind).ok().map(|v| v as f64)
+                    }
+                    (_, other) => other.as_f64(),
+                };
+                operand.map(|operand| {
+                    let amount = op.apply(base as f64, operand).round() as i64;
+                    Value::String(units::format(amount, kind))
+                })
+            }
+            // A missing field counts as zero, so adding sets it
+            (None, Value::Number(_) | Value::String(_)) if matches!(op, Arithmetic::Add) => {
+                Some(operand.clone())
+            }
+            _ => None,
+        };
+        if let Some(updated) = updated {
+            target.insert(field.clone(), updated);
+        }
+    }
+}
+
+/// Keep integers as integers when the result is whole
+fn number_like(original: &Number, result: f64) -> Option<Value> {
+    if original.is_i64() && result.fract() == 0.0 {
+        Some(Value::from(result as i64))
+    } else {
+        Number::from_f64(result).map(Value::Number)
+    }
+}
+
+/// The unit kind of a field, from the unit table or by trying to parse the value
+fn unit_kind(entity_type: &str, field: &str, text: &str) -> Option<UnitKind> {
+    units::fields_for_type(entity_type)
+        .find(|f| f.field == field)
+        .map(|f| f.kind)
+        .or_else(|| {
+            INFERRED_KINDS
+                .iter()
+                .copied()
+                .find(|kind| units::parse(text, *kind).is_ok())
+        })
+}
+
+/// `extend` appends values to inherited arrays
+fn apply_extend(target: &mut Map<String, Value>, extend: &Map<String, Value>) {
+    for (field, values) in extend {
+        let entry = target
+            .entry(field.clone())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if !entry.is_array() {
+            *entry = Value::Array(vec![entry.take()]);
+        }
+        if let Value::Array(arr) = entry {
+            match values {
+                Value::Array(values) => arr.extend(values.iter().cloned()),
+                value => arr.push(value.clone()),
+            }
+        }
+    }
+}
+
+/// `delete` removes values from inherited arrays
+fn apply_delete(target: &mut Map<String, Value>, delete: &Map<String, Value>) {
+    for (field, values) in delete {
+        let values: Vec<&Value> = match values {
+            Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        if let Some(Value::Array(arr)) = target.get_mut(field) {
+            arr.retain(|v| !values.contains(&v));
+        }
+    }
+}
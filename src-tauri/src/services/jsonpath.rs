@@ -0,0 +1,404 @@
+use serde_json::Value;
+
+/// A parsed JSONPath expression.
+///
+/// Supports the subset useful for entity JSON: `$`, `.key`, `['key']`,
+/// `[0]`, `.*` / `[*]`, recursive descent (`..key`, `..*`) and filters on
+/// the children of a node. A filter tests one relative path, either for
+/// existence (`[?(@.count)]`) or against a literal with `==`, `!=`, `<`,
+/// `<=`, `>` or `>=` (`[?(@.type == 'GUN')]`, `[?(@.level >= 2)]`);
+/// `&&`, `||` and functions aren't supported.
+#[derive(Debug, Clone)]
+pub struct JsonPath {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    /// Select from direct children
+    Child(Selector),
+    /// Select from the node and all of its descendants
+    Descendant(Selector),
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Key(String),
+    Index(usize),
+    Wildcard,
+    Filter(Filter),
+}
+
+/// `[?(@.path)]` or `[?(@.path <op> literal)]`
+#[derive(Debug, Clone)]
+struct Filter {
+    path: JsonPath,
+    comparison: Option<(FilterOp, Value)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FilterOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl JsonPath {
+    pub fn parse(text: &str) -> Result<Self, JsonPathError> {
+        let invalid = |reason: &str| JsonPathError::Invalid {
+            path: text.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let mut rest = text
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with '$'"))?;
+        let mut steps = Vec::new();
+
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("..") {
+                let (selector, remaining) = if after.starts_with('[') {
+                    parse_bracket(after).map_err(invalid)?
+                } else {
+                    parse_name(after)
+                };
+                if matches!(&selector, Selector::Key(key) if key.is_empty()) {
+                    return Err(invalid("expected a name after '..'"));
+                }
+                steps.push(Step::Descendant(selector));
+                rest = remaining;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let (selector, remaining) = parse_name(after);
+                if matches!(&selector, Selector::Key(key) if key.is_empty()) {
+                    return Err(invalid("expected a name after '.'"));
+                }
+                steps.push(Step::Child(selector));
+                rest = remaining;
+            } else if rest.starts_with('[') {
+                let (selector, remaining) = parse_bracket(rest).map_err(invalid)?;
+                steps.push(Step::Child(selector));
+                rest = remaining;
+            } else {
+                return Err(invalid("expected '.' or '['"));
+            }
+        }
+
+        Ok(Self { steps })
+    }
+
+    /// Every value the path selects, with its validation path (e.g. "$.flags[1]")
+    pub fn select<'v>(&self, root: &'v Value) -> Vec<(String, &'v Value)> {
+        let mut nodes = vec![("$".to_string(), root)];
+
+        for step in &self.steps {
+            let mut next = Vec::new();
+            for (path, value) in nodes {
+                match step {
+                    Step::Child(selector) => select_children(&path, value, selector, &mut next),
+                    Step::Descendant(selector) => {
+                        let mut subtree = Vec::new();
+                        collect_subtree(path, value, &mut subtree);
+                        for (path, value) in subtree {
+                            select_children(&path, value, selector, &mut next);
+                        }
+                    }
+                }
+            }
+            nodes = next;
+        }
+
+        nodes
+    }
+}
+
+/// Parse a dotted name (or `*`) up to the next '.' or '['
+fn parse_name(text: &str) -> (Selector, &str) {
+    let end = text.find(['.', '[']).unwrap_or(text.len());
+    let name = &text[..end];
+    let selector = if name == "*" {
+        Selector::Wildcard
+    } else {
+        Selector::Key(name.to_string())
+    };
+    (selector, &text[end..])
+}
+
+/// Parse `[n]`, `[*]`, `['key']`, `["key"]` or `[?(filter)]`
+fn parse_bracket(text: &str) -> Result<(Selector, &str), &'static str> {
+    const UNTERMINATED: &str = "unterminated '['";
+    let inner = text.strip_prefix('[').ok_or(UNTERMINATED)?;
+
+    if let Some(body) = inner.strip_prefix("?(") {
+        let end = filter_end(body).ok_or("unterminated filter")?;
+        let rest = body[end + 1..].strip_prefix(']').ok_or(UNTERMINATED)?;
+        return Ok((Selector::Filter(parse_filter(&body[..end])?), rest));
+    }
+
+    if let Some(quote) = inner.chars().next().filter(|c| *c == '\'' || *c == '"') {
+        let body = &inner[1..];
+        let end = body.find(quote).ok_or(UNTERMINATED)?;
+        let rest = body[end + 1..].strip_prefix(']').ok_or(UNTERMINATED)?;
+        return Ok((Selector::Key(body[..end].to_string()), rest));
+    }
+
+    let end = inner.find(']').ok_or(UNTERMINATED)?;
+    let token = inner[..end].trim();
+    let selector = if token == "*" {
+        Selector::Wildcard
+    } else {
+        Selector::Index(
+            token
+                .parse()
+                .map_err(|_| "expected an index, '*', a key or a filter")?,
+        )
+    };
+    Ok((selector, &inner[end + 1..]))
+}
+
+/// Position of the ')' closing a filter, skipping quoted literals and nested brackets
+fn filter_end(body: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, c) in body.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ']') => depth = depth.saturating_sub(1),
+            (None, ')') if depth == 0 => return Some(i),
+            (None, ')') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse the inside of `[?(...)]`
+fn parse_filter(text: &str) -> Result<Filter, &'static str> {
+    const OPERATORS: &[(&str, FilterOp)] = &[
+        ("==", FilterOp::Equal),
+        ("!=", FilterOp::NotEqual),
+        ("<=", FilterOp::LessOrEqual),
+        (">=", FilterOp::GreaterOrEqual),
+        ("<", FilterOp::Less),
+        (">", FilterOp::Greater),
+    ];
+
+    let text = text.trim();
+    let split = text.char_indices().find_map(|(i, _)| {
+        OPERATORS
+            .iter()
+            .find(|(op, _)| text[i..].starts_with(op))
+            .map(|(op, filter_op)| (i, op.len(), *filter_op))
+    });
+    let (left, comparison) = match split {
+        Some((i, len, op)) => {
+            let literal = parse_literal(text[i + len..].trim())
+                .ok_or("filter values must be numbers, quoted strings, true, false or null")?;
+            (text[..i].trim(), Some((op, literal)))
+        }
+        None => (text, None),
+    };
+
+    let relative = left
+        .strip_prefix('@')
+        .ok_or("filters must test a path starting with '@'")?;
+    let path = JsonPath::parse(&format!("${}", relative)).map_err(|_| "invalid path in filter")?;
+    Ok(Filter { path, comparison })
+}
+
+fn parse_literal(text: &str) -> Option<Value> {
+    for quote in ['\'', '"'] {
+        if let Some(body) = text
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return Some(Value::String(body.to_string()));
+        }
+    }
+    serde_json::from_str::<Value>(text)
+        .ok()
+        .filter(|value| !value.is_array() && !value.is_object())
+}
+
+impl Filter {
+    fn matches(&self, value: &Value) -> bool {
+        let selected = self.path.select(value);
+        match &self.comparison {
+            None => !selected.is_empty(),
+            Some((op, literal)) => selected
+                .iter()
+                .any(|(_, selected)| op.compare(selected, literal)),
+        }
+    }
+}
+
+impl FilterOp {
+    fn compare(self, value: &Value, literal: &Value) -> bool {
+        let numbers = value.as_f64().zip(literal.as_f64());
+        match self {
+            FilterOp::Equal => match numbers {
+                Some((a, b)) => a == b,
+                None => value == literal,
+            },
+            FilterOp::NotEqual => match numbers {
+                Some((a, b)) => a != b,
+                None => value != literal,
+            },
+            FilterOp::Less => numbers.is_some_and(|(a, b)| a < b),
+            FilterOp::LessOrEqual => numbers.is_some_and(|(a, b)| a <= b),
+            FilterOp::Greater => numbers.is_some_and(|(a, b)| a > b),
+            FilterOp::GreaterOrEqual => numbers.is_some_and(|(a, b)| a >= b),
+        }
+    }
+}
+
+fn select_children<'v>(
+    path: &str,
+    value: &'v Value,
+    selector: &Selector,
+    out: &mut Vec<(String, &'v Value)>,
+) {
+    match (selector, value) {
+        (Selector::Key(key), Value::Object(obj)) => {
+            if let Some(child) = obj.get(key) {
+                out.push((child_path(path, key), child));
+            }
+        }
+        (Selector::Index(index), Value::Array(arr)) => {
+            if let Some(child) = arr.get(*index) {
+                out.push((format!("{}[{}]", path, index), child));
+            }
+        }
+        (Selector::Wildcard, Value::Object(obj)) => {
+            for (key, child) in obj {
+                out.push((child_path(path, key), child));
+            }
+        }
+        (Selector::Wildcard, Value::Array(arr)) => {
+            for (i, child) in arr.iter().enumerate() {
+                out.push((format!("{}[{}]", path, i), child));
+            }
+        }
+        (Selector::Filter(filter), Value::Object(obj)) => {
+            for (key, child) in obj {
+                if filter.matches(child) {
+                    out.push((child_path(path, key), child));
+                }
+            }
+        }
+        (Selector::Filter(filter), Value::Array(arr)) => {
+            for (i, child) in arr.iter().enumerate() {
+                if filter.matches(child) {
+                    out.push((format!("{}[{}]", path, i), child));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A node and all of its descendants, in document order
+fn collect_subtree<'v>(path: String, value: &'v Value, out: &mut Vec<(String, &'v Value)>) {
+    out.push((path.clone(), value));
+    match value {
+        Value::Object(obj) => {
+            for (key, child) in obj {
+                collect_subtree(child_path(&path, key), child, out);
+            }
+        }
+        Value::Array(arr) => {
+            for (i, child) in arr.iter().enumerate() {
+                collect_subtree(format!("{}[{}]", path, i), child, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Path of an object member: `$.a.key`, or `$.a["key"]` when the key isn't a plain identifier
+pub fn child_path(path: &str, key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}[{}]", path, Value::String(key.to_string()))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JsonPathError {
+    #[error("invalid JSONPath '{path}': {reason}")]
+    Invalid { path: String, reason: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(path: &str, value: &Value) -> Vec<String> {
+        JsonPath::parse(path)
+            .unwrap()
+            .select(value)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    #[test]
+    fn children_indices_and_descendants() {
+        let item = json!({
+            "id": "knife",
+            "flags": ["SHEATH_KNIFE", "STAB"],
+            "melee_damage": { "cut": 8 },
+            "weird key": 1
+        });
+
+        assert_eq!(paths("$.flags[1]", &item), ["$.flags[1]"]);
+        assert_eq!(paths("$['weird key']", &item), ["$[\"weird key\"]"]);
+        assert_eq!(paths("$.flags[*]", &item).len(), 2);
+        assert_eq!(paths("$..cut", &item), ["$.melee_damage.cut"]);
+        assert!(paths("$.missing", &item).is_empty());
+    }
+
+    #[test]
+    fn filters() {
+        let recipe = json!({
+            "qualities": [{ "id": "CUT", "level": 1 }, { "id": "HAMMER", "level": 3 }],
+            "components": [[["rag", 2]], [["string_6", 1]]]
+        });
+
+        assert_eq!(
+            paths("$.qualities[?(@.id == 'HAMMER')]", &recipe),
+            ["$.qualities[1]"]
+        );
+        assert_eq!(
+            paths("$.qualities[?(@.level >= 2)].id", &recipe),
+            ["$.qualities[1].id"]
+        );
+        assert_eq!(paths("$.qualities[?(@.id != \"CUT\")]", &recipe).len(), 1);
+        assert_eq!(paths("$.qualities[?(@.level)]", &recipe).len(), 2);
+        assert_eq!(
+            paths("$.components[*][?(@[0] == 'rag')]", &recipe),
+            ["$.components[0][0]"]
+        );
+    }
+
+    #[test]
+    fn invalid_paths() {
+        assert!(JsonPath::parse("flags").is_err());
+        assert!(JsonPath::parse("$.flags[").is_err());
+        assert!(JsonPath::parse("$.a[?(@.b == 1]").is_err());
+        assert!(JsonPath::parse("$.a[?(b == 1)]").is_err());
+        assert!(JsonPath::parse("$.a[?(@.b == CUT)]").is_err());
+    }
+}
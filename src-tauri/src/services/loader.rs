@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use uuid::Uuid;
 use walkdir::WalkDir;
 
+use super::rules;
 use crate::models::{
     ContentPack, Entity, EntityKey, LoadStats, PackLoadResult, PackMetadata,
 };
@@ -40,6 +41,11 @@ pub fn load_content_pack(
         errors: Vec::new(),
     };
 
+    let rules = rules::load_rules(&path).unwrap_or_else(|e| {
+        stats.errors.push(e.to_string());
+        Vec::new()
+    });
+
     // Find all JSON files in the pack
     let json_files = find_json_files(&path, exclude_dirs.as_deref());
     stats.files_scanned = json_files.len();
@@ -73,6 +79,7 @@ pub fn load_content_pack(
         name,
         entity_tree,
         load_stats: stats,
+        rules,
     })
 }
 
@@ -94,6 +101,7 @@ pub fn create_pack_from_result(
     let name = name_override.unwrap_or_else(|| result.name.clone());
     let mut pack = ContentPack::new(result.pack_id, name, path.clone(), read_only);
     pack.metadata = metadata;
+    pack.rules = result.rules.clone();
 
     // Reload entities (we need to do this again since PackLoadResult doesn't contain full entities)
     let json_files = find_json_files(&path, exclude_dirs.as_deref());
@@ -101,6 +109,10 @@ pub fn create_pack_from_result(
         if let Ok(entities) = load_entities_from_file(&file_path, &path) {
             for entity in entities {
                 let key = entity.key();
+                pack.disk_keys
+                    .entry(entity.source_file.clone())
+                    .or_default()
+                    .insert(key.clone());
                 let unique_key = if pack.entities.contains_key(&key) {
                     make_unique_key(&key, &entity.source_file, &pack.entities)
                 } else {
@@ -248,8 +260,11 @@ fn find_json_files(path: &Path, exclude_dirs: Option<&[String]>) -> Vec<PathBuf>
     {
         let path = entry.path();
         if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
-            // Skip modinfo.json as it's metadata, not entity data
-            if path.file_name().is_some_and(|n| n != "modinfo.json") {
+            // Skip modinfo.json and the rule file as they're metadata, not entity data
+            if path
+                .file_name()
+                .is_some_and(|n| n != "modinfo.json" && n != rules::RULES_FILE)
+            {
                 files.push(path.to_path_buf());
             }
         }
@@ -0,0 +1,313 @@
+use serde_json::{json, Map, Value};
+
+use super::units;
+use super::validator::ITEM_TYPES;
+use crate::models::{Fix, PatchOperation};
+
+/// A field whose old form is deprecated or obsolete in BN
+pub struct Migration {
+    /// Game release whose JSON stopped using the old form
+    pub since: &'static str,
+    /// Entity types the rule applies to (empty means every type)
+    pub types: &'static [&'static str],
+    pub field: &'static str,
+    pub kind: MigrationKind,
+    /// What to use instead, shown in warnings
+    pub replacement: &'static str,
+}
+
+/// How a deprecated field is rewritten to the current form
+pub enum MigrationKind {
+    /// The game ignores the field, so it can be dropped
+    Remove,
+    /// Rewrite the entity; returns false if the value can't be converted
+    Rewrite(fn(&mut Map<String, Value>) -> bool),
+}
+
+/// Deprecated fields, oldest first
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        since: "0.B",
+        types: ITEM_TYPES,
+        field: "rarity",
+        kind: MigrationKind::Remove,
+        replacement: "item groups to control spawn rates",
+    },
+    Migration {
+        since: "0.E",
+        types: &[],
+        field: "name_plural",
+        kind: MigrationKind::Rewrite(migrate_name_plural),
+        replacement: "'name': { \"str\": ..., \"str_pl\": ... }",
+    },
+    Migration {
+        since: "0.F",
+        types: ITEM_TYPES,
+        field: "bashing",
+        kind: MigrationKind::Rewrite(migrate_item_bashing),
+        replacement: "'melee_damage': { \"bash\": ... }",
+    },
+    Migration {
+        since: "0.F",
+        types: ITEM_TYPES,
+        field: "cutting",
+        kind: MigrationKind::Rewrite(migrate_item_cutting),
+        replacement: "'melee_damage': { \"cut\": ... }",
+    },
+    Migration {
+        since: "0.F",
+        types: &["MONSTER"],
+        field: "melee_cut",
+        kind: MigrationKind::Rewrite(migrate_monster_melee_cut),
+        replacement: "a 'cut' entry in 'melee_damage'",
+    },
+    Migration {
+        since: "0.F",
+        types: ITEM_TYPES,
+        field: "use_action",
+        kind: MigrationKind::Rewrite(migrate_use_action),
+        replacement: "a single action ID string instead of a one-element array",
+    },
+];
+
+/// A deprecated field found in an entity
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    /// JSON path to the field
+    pub path: String,
+    pub message: String,
+    /// Rewrite to the current form, if the value can be converted
+    pub fix: Option<Fix>,
+}
+
+/// Find deprecated fields in an entity, including legacy integer unit values
+pub fn find_deprecated(json: &Value) -> Vec<Deprecation> {
+    let entity_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+    let mut found = Vec::new();
+
+    for migration in migrations_for_type(entity_type) {
+        let Some(value) = json.get(migration.field) else {
+            continue;
+        };
+        // use_action is only deprecated in its one-element array form
+        if migration.field == "use_action" && single_action(value).is_none() {
+            continue;
+        }
+        let mut migrated = json.clone();
+        let applied = migrated
+            .as_object_mut()
+            .is_some_and(|obj| apply_migration(obj, migration));
+        let fix = applied.then(|| {
+            Fix::new(
+                format!("Migrate '{}' to the current form", migration.field),
+                top_level_changes(json, &migrated),
+            )
+        });
+        found.push(Deprecation {
+            path: format!("$.{}", migration.field),
+            message: format!(
+                "'{}' is deprecated since {}; use {}",
+                migration.field, migration.since, migration.replacement
+            ),
+            fix,
+        });
+    }
+
+    for field in units::fields_for_type(entity_type) {
+        let Some(amount) = json.get(field.field).and_then(|v| v.as_i64()) else {
+            continue;
+        };
+        let text = field.legacy_to_string(amount);
+        let message = match &text {
+            Some(text) => format!(
+                "Plain integer '{}' is deprecated; use a unit string (\"{}\")",
+                field.field, text
+            ),
+            None if field.legacy != units::LegacyFormat::None => format!(
+                "Plain integer '{}' is deprecated; use a unit string",
+                field.field
+            ),
+            None => continue,
+        };
+        let fix = text.map(|text| {
+            Fix::new(
+                format!("Replace {} with \"{}\"", amount, text),
+                vec![PatchOperation::Replace {
+                    path: format!("/{}", field.field),
+                    value: Value::String(text),
+                }],
+            )
+        });
+        found.push(Deprecation {
+            path: format!("$.{}", field.field),
+            message,
+            fix,
+        });
+    }
+
+    found
+}
+
+/// Rewrite deprecated fields of an entity to their current form.
+/// Returns the number of fields migrated.
+pub fn migrate_entity(json: &mut Value) -> usize {
+    let entity_type = json
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let mut migrated = units::normalize_legacy_values(json);
+
+    let Some(obj) = json.as_object_mut() else {
+        return migrated;
+    };
+
+    for migration in migrations_for_type(&entity_type) {
+        if apply_migration(obj, migration) {
+            migrated += 1;
+        }
+    }
+
+    migrated
+}
+
+/// Apply one migration; returns false if the field is absent or can't be converted
+fn apply_migration(obj: &mut Map<String, Value>, migration: &Migration) -> bool {
+    if !obj.contains_key(migration.field) {
+        return false;
+    }
+    match migration.kind {
+        MigrationKind::Remove => obj.remove(migration.field).is_some(),
+        MigrationKind::Rewrite(rewrite) => rewrite(obj),
+    }
+}
+
+/// Patch operations turning `before` into `after`, one per changed top-level field
+fn top_level_changes(before: &Value, after: &Value) -> Vec<PatchOperation> {
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return Vec::new();
+    };
+    let pointer = |key: &str| format!("/{}", key.replace('~', "~0").replace('/', "~1"));
+
+    let mut operations: Vec<PatchOperation> = before
+        .keys()
+        .filter(|key| !after.contains_key(*key))
+        .map(|key| PatchOperation::Remove { path: pointer(key) })
+        .collect();
+    for (key, value) in after {
+        match before.get(key) {
+            None => operations.push(PatchOperation::Add {
+                path: pointer(key),
+                value: value.clone(),
+            }),
+            Some(old) if old != value => operations.push(PatchOperation::Replace {
+                path: pointer(key),
+                value: value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    operations
+}
+
+/// Migrations that apply to an entity type
+fn migrations_for_type(entity_type: &str) -> impl Iterator<Item = &'static Migration> + '_ {
+    MIGRATIONS
+        .iter()
+        .filter(move |m| m.types.is_empty() || m.types.contains(&entity_type))
+}
+
+/// `"name": "x", "name_plural": "xs"` → `"name": { "str": "x", "str_pl": "xs" }`
+fn migrate_name_plural(obj: &mut Map<String, Value>) -> bool {
+    let Some(plural) = obj.get("name_plural").cloned() else {
+        return false;
+    };
+    match obj.get_mut("name") {
+        Some(Value::String(name)) => {
+            let name = std::mem::take(name);
+            obj.insert("name".to_string(), json!({ "str": name, "str_pl": plural }));
+        }
+        Some(Value::Object(name)) if !name.contains_key("str_pl") => {
+            name.insert("str_pl".to_string(), plural);
+        }
+        _ => return false,
+    }
+    obj.remove("name_plural");
+    true
+}
+
+fn migrate_item_bashing(obj: &mut Map<String, Value>) -> bool {
+    move_into_melee_damage(obj, "bashing", "bash")
+}
+
+fn migrate_item_cutting(obj: &mut Map<String, Value>) -> bool {
+    move_into_melee_damage(obj, "cutting", "cut")
+}
+
+/// Move an item damage field into the `melee_damage` object
+fn move_into_melee_damage(obj: &mut Map<String, Value>, field: &str, damage_type: &str) -> bool {
+    if !obj.get(field).is_some_and(|v| v.is_number()) {
+        return false;
+    }
+    match obj.get("melee_damage") {
+        Some(Value::Object(existing)) if !existing.contains_key(damage_type) => {}
+        Some(_) => return false,
+        None => {}
+    }
+
+    let amount = obj.remove(field).unwrap_or_default();
+    // Zero damage is the default, so it doesn't need an entry
+    if amount.as_f64() != Some(0.0) {
+        if let Value::Object(melee_damage) = obj
+            .entry("melee_damage")
+            .or_insert_with(|| Value::Object(Map::new()))
+        {
+            melee_damage.insert(damage_type.to_string(), amount);
+        }
+    }
+    true
+}
+
+/// `"melee_cut": n` → a cut entry in the monster's `melee_damage` array
+fn migrate_monster_melee_cut(obj: &mut Map<String, Value>) -> bool {
+    let Some(amount) = obj.get("melee_cut").filter(|v| v.is_number()).cloned() else {
+        return false;
+    };
+    match obj.get("melee_damage") {
+        Some(Value::Array(instances))
+            if !instances
+                .iter()
+                .any(|d| d.get("damage_type").and_then(|t| t.as_str()) == Some("cut")) => {}
+        Some(_) => return false,
+        None => {}
+    }
+
+    obj.remove("melee_cut");
+    if amount.as_f64() != Some(0.0) {
+        if let Value::Array(instances) = obj
+            .entry("melee_damage")
+            .or_insert_with(|| Value::Array(Vec::new()))
+        {
+            instances.push(json!({ "damage_type": "cut", "amount": amount }));
+        }
+    }
+    true
+}
+
+/// `"use_action": ["X"]` → `"use_action": "X"`
+fn migrate_use_action(obj: &mut Map<String, Value>) -> bool {
+    let Some(action) = obj.get("use_action").and_then(single_action) else {
+        return false;
+    };
+    obj.insert("use_action".to_string(), action);
+    true
+}
+
+/// The action of a one-element `use_action` array
+fn single_action(value: &Value) -> Option<Value> {
+    match value.as_array()?.as_slice() {
+        [action] if action.is_string() => Some(action.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,228 @@
+use serde_json::{Map, Value};
+
+/// Fields every minimized entity keeps as they are
+const IDENTITY_FIELDS: &[&str] = &["type", "id", "abstract", "copy-from"];
+
+/// Rewrite an entity as `copy-from` its base plus only what differs.
+///
+/// `resolved` is the entity's effective JSON and `base` the resolved
+/// definition it builds on. Arrays that only gained or lost entries use
+/// `extend` / `delete`, and objects where only whole numbers changed use
+/// `relative`, so unrelated parts keep following the base. Everything else
+/// that differs is kept as a plain value.
+pub fn minimize(
+    json: &Value,
+    resolved: &Value,
+    parent_id: &str,
+    base: &Value,
+) -> Result<Value, MinimizeError> {
+    let (Some(entity), Some(resolved), Some(base)) =
+        (json.as_object(), resolved.as_object(), base.as_object())
+    else {
+        return Err(MinimizeError::NotAnObject);
+    };
+
+    let mut result = Map::new();
+    for field in IDENTITY_FIELDS {
+        if let Some(value) = entity.get(*field) {
+            result.insert(field.to_string(), value.clone());
+        }
+    }
+    result.insert(
+        "copy-from".to_string(),
+        Value::String(parent_id.to_string()),
+    );
+
+    // There is no way to remove an inherited field that isn't an array
+    if let Some(field) = base
+        .keys()
+        .find(|field| !IDENTITY_FIELDS.contains(&field.as_str()) && !resolved.contains_key(*field))
+    {
+        return Err(MinimizeError::Uninheritable(field.clone()));
+    }
+
+    let mut relative = Map::new();
+    let mut extend = Map::new();
+    let mut delete = Map::new();
+
+    for (field, value) in resolved {
+        if IDENTITY_FIELDS.contains(&field.as_str()) || base.get(field) == Some(value) {
+            continue;
+        }
+        match (base.get(field), value) {
+            (Some(Value::Array(before)), Value::Array(after)) => {
+                match array_changes(before, after) {
+                    Some((added, removed)) => {
+                        if !added.is_empty() {
+                            extend.insert(field.clone(), Value::Array(added));
+                        }
+                        if !removed.is_empty() {
+                            delete.insert(field.clone(), Value::Array(removed));
+                        }
+                    }
+                    None => {
+                        result.insert(field.clone(), value.clone());
+                    }
+                }
+            }
+            (Some(Value::Object(before)), Value::Object(after)) => {
+                match number_changes(before, after) {
+                    Some(changes) => {
+                        relative.insert(field.clone(), Value::Object(changes));
+                    }
+                    None => {
+                        result.insert(field.clone(), value.clone());
+                    }
+                }
+            }
+            _ => {
+                result.insert(field.clone(), value.clone());
+            }
+        }
+    }
+
+    for (name, directive) in [
+        ("relative", relative),
+        ("extend", extend),
+        ("delete", delete),
+    ] {
+        if !directive.is_empty() {
+            result.insert(name.to_string(), Value::Object(directive));
+        }
+    }
+    Ok(Value::Object(result))
+}
+
+/// Entries to `extend` and `delete` that turn `before` into `after`, or None
+/// if order or duplicates mean it has to be restated. `delete` removes every
+/// equal entry after `extend` appends, which is how the game applies them.
+fn array_changes(before: &[Value], after: &[Value]) -> Option<(Vec<Value>, Vec<Value>)> {
+    let added: Vec<Value> = after
+        .iter()
+        .filter(|value| !before.contains(value))
+        .cloned()
+        .collect();
+    let mut removed: Vec<Value> = Vec::new();
+    for value in before {
+        if !after.contains(value) && !removed.contains(value) {
+            removed.push(value.clone());
+        }
+    }
+
+    let mut rebuilt: Vec<Value> = before.iter().chain(&added).cloned().collect();
+    rebuilt.retain(|value| !removed.contains(value));
+    if rebuilt != after {
+        return None;
+    }
+    // Restating a short array is clearer than a pair of directives
+    if added.len() + removed.len() >= after.len() {
+        return None;
+    }
+    Some((added, removed))
+}
+
+/// `relative` amounts that turn `before` into `after`, when the two have
+/// the same shape and only whole numbers differ
+fn number_changes(
+    before: &Map<String, Value>,
+    after: &Map<String, Value>,
+) -> Option<Map<String, Value>> {
+    if before.len() != after.len() {
+        return None;
+    }
+
+    let mut changes = Map::new();
+    for (field, after) in after {
+        let before = before.get(field)?;
+        if before == after {
+            continue;
+        }
+        let change = match (before, after) {
+            (Value::Object(before), Value::Object(after)) => {
+                Value::Object(number_changes(before, after)?)
+            }
+            (Value::Number(before), Value::Number(after)) => {
+                Value::from(after.as_i64()?.checked_sub(before.as_i64()?)?)
+            }
+            _ => return None,
+        };
+        changes.insert(field.clone(), change);
+    }
+    Some(changes)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MinimizeError {
+    #[error("Entity is not a JSON object")]
+    NotAnObject,
+
+    #[error("'{0}' is in the base but not in the entity, and can't be removed with copy-from")]
+    Uninheritable(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn base() -> Value {
+        json!({
+            "type": "GENERIC",
+            "id": "stick",
+            "name": "stick",
+            "weight": "1 kg",
+            "flags": ["A", "B", "C", "D"],
+            "melee_damage": { "bash": 5, "cut": 2 }
+        })
+    }
+
+    #[test]
+    fn keeps_only_what_differs() {
+        let json = json!({ "type": "GENERIC", "id": "big_stick", "copy-from": "stick" });
+        let resolved = json!({
+            "type": "GENERIC",
+            "id": "big_stick",
+            "name": "stick",
+            "weight": "2 kg",
+            "flags": ["A", "B", "C", "E"],
+            "melee_damage": { "bash": 8, "cut": 2 }
+        });
+
+        assert_eq!(
+            minimize(&json, &resolved, "stick", &base()).unwrap(),
+            json!({
+                "type": "GENERIC",
+                "id": "big_stick",
+                "copy-from": "stick",
+                "weight": "2 kg",
+                "relative": { "melee_damage": { "bash": 3 } },
+                "extend": { "flags": ["E"] },
+                "delete": { "flags": ["D"] }
+            })
+        );
+    }
+
+    #[test]
+    fn reordered_arrays_are_restated() {
+        let json = json!({ "type": "GENERIC", "id": "big_stick" });
+        let mut resolved = base();
+        resolved["id"] = json!("big_stick");
+        resolved["flags"] = json!(["D", "C", "B", "A"]);
+
+        let minimized = minimize(&json, &resolved, "stick", &base()).unwrap();
+        assert_eq!(minimized["flags"], json!(["D", "C", "B", "A"]));
+        assert!(minimized.get("extend").is_none());
+    }
+
+    #[test]
+    fn removed_fields_cant_be_inherited() {
+        let json = json!({ "type": "GENERIC", "id": "big_stick" });
+        let mut resolved = base();
+        resolved.as_object_mut().unwrap().remove("weight");
+
+        assert!(matches!(
+            minimize(&json, &resolved, "stick", &base()),
+            Err(MinimizeError::Uninheritable(field)) if field == "weight"
+        ));
+    }
+}
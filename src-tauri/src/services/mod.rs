@@ -1,2 +1,22 @@
+pub mod compare;
+pub mod diff;
+pub mod fixes;
+pub mod flags;
+pub mod fuzzy;
+pub mod git;
+pub mod history;
+pub mod ids;
+pub mod inheritance;
+pub mod jsonpath;
 pub mod loader;
+pub mod migrations;
+pub mod minimize;
+pub mod palette;
+pub mod query;
+pub mod references;
+pub mod reorganize;
+pub mod replace;
+pub mod rules;
+pub mod search_index;
+pub mod units;
 pub mod validator;
@@ -0,0 +1,386 @@
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::models::{
+    PaletteData, SymbolCandidate, SymbolDefinition, SymbolMapping, SymbolSection, SYMBOL_SECTIONS,
+};
+
+/// Resolve a palette and everything it includes.
+///
+/// Included palettes are applied first, depth first and in list order, then
+/// the palette's own definitions. A `param` or `distribution` entry in
+/// `palettes` is chosen when the map is generated, so every palette it can
+/// choose is applied. `lookup` finds a palette's JSON by ID; palettes it
+/// doesn't find are reported as missing.
+pub fn resolve(
+    id: &str,
+    json: &Value,
+    lookup: impl Fn(&str) -> Option<Value>,
+) -> Result<PaletteData, PaletteError> {
+    let mut applied = Vec::new();
+    let mut missing = Vec::new();
+    collect(
+        id,
+        json,
+        &Map::new(),
+        &lookup,
+        &mut vec![id.to_string()],
+        &mut applied,
+        &mut missing,
+    )?;
+
+    // Parameters are shared by the whole tree; the outermost definition wins
+    let mut parameters = Map::new();
+    for (_, palette) in &applied {
+        if let Some(own) = palette.get("parameters").and_then(|v| v.as_object()) {
+            parameters.extend(own.clone());
+        }
+    }
+
+    let mut symbols: BTreeMap<String, Vec<SymbolDefinition>> = BTreeMap::new();
+    for (palette_id, palette) in &applied {
+        for section in SYMBOL_SECTIONS {
+            let Some(entries) = palette.get(section.name).and_then(|v| v.as_object()) else {
+                continue;
+            };
+            for (symbol, value) in entries {
+                symbols
+                    .entry(symbol.clone())
+                    .or_default()
+                    .push(SymbolDefinition {
+                        section: section.name.to_string(),
+                        palette: palette_id.clone(),
+                        candidates: mapping_candidates(value, section, &parameters),
+                        value: value.clone(),
+                    });
+            }
+        }
+    }
+
+    let mappings = symbols
+        .into_iter()
+        .map(|(symbol, definitions)| {
+            let winner = |section: &str| {
+                definitions
+                    .iter()
+                    .rev()
+                    .find(|d| d.section == section)
+                    .and_then(|d| d.candidates.first())
+                    .and_then(|c| c.id.clone())
+            };
+            SymbolMapping {
+                terrain: winner("terrain"),
+                furniture: winner("furniture"),
+                symbol,
+                definitions,
+            }
+        })
+        .collect();
+
+    Ok(PaletteData {
+        id: id.to_string(),
+        mappings,
+        includes: applied
+            .iter()
+            .map(|(palette_id, _)| palette_id.clone())
+            .filter(|palette_id| palette_id != id)
+            .collect(),
+        missing,
+    })
+}
+
+/// Add the palettes `json` includes, then `json` itself, to `applied`.
+/// A palette reached again by another path is only applied the first time.
+fn collect(
+    id: &str,
+    json: &Value,
+    outer_parameters: &Map<String, Value>,
+    lookup: &impl Fn(&str) -> Option<Value>,
+    stack: &mut Vec<String>,
+    applied: &mut Vec<(String, Value)>,
+    missing: &mut Vec<String>,
+) -> Result<(), PaletteError> {
+    let obj = json
+        .as_object()
+        .ok_or_else(|| PaletteError::NotAnObject(id.to_string()))?;
+
+    let mut parameters = obj
+        .get("parameters")
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+    parameters.extend(outer_parameters.clone());
+
+    for entry in obj
+        .get("palettes")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        for candidate in candidates(entry, &[], &parameters, None) {
+            let Some(included) = candidate.id else {
+                continue;
+            };
+            if stack.contains(&included) {
+                let mut cycle = stack.clone();
+                cycle.push(included);
+                return Err(PaletteError::Cycle(cycle));
+            }
+            if applied
+                .iter()
+                .any(|(applied_id, _)| *applied_id == included)
+                || missing.contains(&included)
+            {
+                continue;
+            }
+            match lookup(&included) {
+                Some(palette) => {
+                    stack.push(included.clone());
+                    collect(
+                        &included,
+                        &palette,
+                        &parameters,
+                        lookup,
+                        stack,
+                        applied,
+                        missing,
+                    )?;
+                    stack.pop();
+                }
+                None => missing.push(included),
+            }
+        }
+    }
+
+    applied.push((id.to_string(), json.clone()));
+    Ok(())
+}
+
+/// What a symbol's entry in `section` may place: "t_floor",
+/// ["t_floor", "t_grass"], [["t_floor", 2], "t_grass"],
+/// {"param": ..., "fallback": ...}, {"distribution": [...]} or an object
+/// naming its content in one of the section's fields (e.g.
+/// {"item": "trash", "chance": 50}). `parameters` are the palette and
+/// mapgen parameters a `param` entry can refer to.
+pub fn mapping_candidates(
+    value: &Value,
+    section: &SymbolSection,
+    parameters: &Map<String, Value>,
+) -> Vec<SymbolCandidate> {
+    let fields: Vec<&str> = section.fields.iter().map(|(field, _)| *field).collect();
+    candidates(value, &fields, parameters, None)
+}
+
+fn candidates(
+    value: &Value,
+    id_fields: &[&str],
+    parameters: &Map<String, Value>,
+    parameter: Option<&str>,
+) -> Vec<SymbolCandidate> {
+    let candidate = |id: Option<&str>, weight: i64| SymbolCandidate {
+        id: id.map(String::from),
+        weight,
+        parameter: parameter.map(String::from),
+    };
+
+    match value {
+        Value::String(id) => vec![candidate(Some(id), 1)],
+        Value::Array(entries) => entries
+            .iter()
+            .flat_map(|entry| match entry.as_array().map(Vec::as_slice) {
+                Some([Value::String(id), weight]) => {
+                    vec![candidate(Some(id), weight.as_i64().unwrap_or(1))]
+                }
+                _ => candidates(entry, id_fields, parameters, parameter),
+            })
+            .collect(),
+        Value::Object(obj) => {
+            if let Some(name) = obj.get("param").and_then(|v| v.as_str()) {
+                // A parameter defined by an outer mapgen isn't known here,
+                // so only the fallback can be told
+                let default = parameters
+                    .get(name)
+                    .and_then(|p| p.get("default"))
+                    .or_else(|| obj.get("fallback"));
+                return default
+                    .map(|default| candidates(default, id_fields, parameters, Some(name)))
+                    .unwrap_or_default();
+            }
+            if let Some(distribution) = obj.get("distribution") {
+                return candidates(distribution, id_fields, parameters, parameter);
+            }
+
+            let named: Vec<SymbolCandidate> = id_fields
+                .iter()
+                .filter_map(|field| field.split('.').try_fold(value, |v, key| v.get(key)))
+                .flat_map(|value| candidates(value, id_fields, parameters, parameter))
+                .collect();
+            if named.is_empty() {
+                vec![candidate(None, 1)]
+            } else {
+                named
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Palettes defined in a game's `data/json`, for looking up palettes
+/// without loading the base game as a pack. Scanned once per game path.
+#[derive(Debug, Default)]
+pub struct PaletteCatalog {
+    game_path: Option<PathBuf>,
+    palettes: HashMap<String, Value>,
+}
+
+impl PaletteCatalog {
+    pub fn scan(game_path: &Path) -> Self {
+        let mut palettes = HashMap::new();
+        let data_path = game_path.join("data").join("json");
+        for entry in WalkDir::new(&data_path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        {
+            let Ok(content) = fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            // Only parse files that can have a palette in them
+            if !content.contains("\"palette\"") {
+                continue;
+            }
+            let Ok(json) = serde_json::from_str::<Value>(&content) else {
+                continue;
+            };
+            let objects = match json {
+                Value::Array(arr) => arr,
+                other => vec![other],
+            };
+            for obj in objects {
+                if obj.get("type").and_then(|v| v.as_str()) != Some("palette") {
+                    continue;
+                }
+                if let Some(id) = obj.get("id").and_then(|v| v.as_str()).map(String::from) {
+                    palettes.insert(id, obj);
+                }
+            }
+        }
+
+        Self {
+            game_path: Some(game_path.to_path_buf()),
+            palettes,
+        }
+    }
+
+    /// Game path the catalog was scanned from
+    pub fn game_path(&self) -> Option<&Path> {
+        self.game_path.as_deref()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Value> {
+        self.palettes.get(id)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaletteError {
+    #[error("Palette '{0}' is not a JSON object")]
+    NotAnObject(String),
+
+    #[error("Palettes include each other: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn library(id: &str) -> Option<Value> {
+        match id {
+            "walls" => Some(json!({
+                "type": "palette",
+                "id": "walls",
+                "terrain": { ".": "t_dirt", "#": [["t_wall", 3], "t_wall_wood"] }
+            })),
+            "house" => Some(json!({
+                "type": "palette",
+                "id": "house",
+                "palettes": ["walls"],
+                "terrain": { ".": "t_floor" },
+                "items": { "x": [{ "item": "trash", "chance": 5 }] }
+            })),
+            "loop" => Some(json!({ "type": "palette", "id": "loop", "palettes": ["root"] })),
+            _ => None,
+        }
+    }
+
+    fn mapping<'a>(palette: &'a PaletteData, symbol: &str) -> &'a SymbolMapping {
+        palette
+            .mappings
+            .iter()
+            .find(|m| m.symbol == symbol)
+            .unwrap()
+    }
+
+    #[test]
+    fn later_palettes_win_and_keep_every_definition() {
+        let root = json!({ "type": "palette", "id": "root", "palettes": ["house", "missing"] });
+        let palette = resolve("root", &root, library).unwrap();
+
+        assert_eq!(palette.includes, ["walls", "house"]);
+        assert_eq!(palette.missing, ["missing"]);
+        let floor = mapping(&palette, ".");
+        assert_eq!(floor.terrain.as_deref(), Some("t_floor"));
+        assert_eq!(floor.definitions.len(), 2);
+        assert_eq!(floor.definitions[0].palette, "walls");
+    }
+
+    #[test]
+    fn weighted_and_object_entries() {
+        let root = json!({ "type": "palette", "id": "root", "palettes": ["house"] });
+        let palette = resolve("root", &root, library).unwrap();
+
+        let wall = &mapping(&palette, "#").definitions[0];
+        let weights: Vec<_> = wall
+            .candidates
+            .iter()
+            .map(|c| (c.id.as_deref().unwrap(), c.weight))
+            .collect();
+        assert_eq!(weights, [("t_wall", 3), ("t_wall_wood", 1)]);
+        let items = &mapping(&palette, "x").definitions[0];
+        assert_eq!(items.candidates[0].id.as_deref(), Some("trash"));
+    }
+
+    #[test]
+    fn parameters_and_distributions() {
+        let root = json!({
+            "type": "palette",
+            "id": "root",
+            "palettes": [{ "distribution": [["walls", 1], ["house", 1]] }],
+            "parameters": {
+                "floor": { "type": "ter_str_id", "default": { "distribution": [["t_a", 1], ["t_b", 4]] } }
+            },
+            "terrain": { "f": { "param": "floor", "fallback": "t_c" } }
+        });
+        let palette = resolve("root", &root, library).unwrap();
+
+        assert_eq!(palette.includes, ["walls", "house"]);
+        let candidates = &mapping(&palette, "f").definitions[0].candidates;
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[1].weight, 4);
+        assert_eq!(candidates[1].parameter.as_deref(), Some("floor"));
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let root = json!({ "type": "palette", "id": "root", "palettes": ["loop"] });
+        assert!(matches!(
+            resolve("root", &root, library),
+            Err(PaletteError::Cycle(_))
+        ));
+    }
+}
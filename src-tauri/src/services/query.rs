@@ -0,0 +1,672 @@
+use regex::Regex;
+use serde_json::Value;
+
+use super::fuzzy::fuzzy_score;
+use super::jsonpath::{child_path, JsonPath, JsonPathError};
+use super::units::{self, LegacyFormat, UnitKind, INFERRED_KINDS};
+
+/// An entity as seen by a query
+pub struct Candidate<'a> {
+    pub id: &'a str,
+    pub entity_type: &'a str,
+    pub display_name: Option<&'a str>,
+    /// Effective JSON (copy-from resolved)
+    pub json: &'a Value,
+}
+
+/// A parsed search query: a list of terms combined with `AND` (implicit between terms),
+/// `OR`, `NOT` / `-term` and parentheses. Terms are:
+///
+/// - `word`: fuzzy match on the ID or display name (see [`fuzzy_score`])
+/// - `"some words"`: substring of the ID or display name, matched literally
+///   even for a single word
+/// - `/regex/`: regex on the ID or display name
+/// - `field:value`: a value under the field equals `value` (case-insensitive,
+///   `*` matches anything), e.g. `type:ARMOR`, `flags:WATERPROOF`
+/// - `field=value`: exact match
+/// - `field>value`, `>=`, `<`, `<=`: numeric comparison; unit strings
+///   compare by amount, e.g. `weight>2kg`, `volume<=500ml`
+/// - `field~regex`: a string under the field matches the regex
+/// - `$.json.path`: the JSONPath selects something; JSONPaths can also be
+///   used as the field of any predicate, e.g. `$.melee_damage.bash>10`
+///
+/// Fields are dotted paths from the entity root (`name.str`, `melee_damage.bash`).
+/// Predicates test every value under what the field selects, so
+/// `flags:X` matches an element of the `flags` array.
+#[derive(Debug)]
+pub struct Query {
+    expr: Option<Expr>,
+    /// Plain-text words every match has, used for relevance ranking
+    text: String,
+}
+
+#[derive(Debug)]
+enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug)]
+enum Term {
+    /// Fuzzy match on a word
+    Text(String),
+    /// Literal match on a quoted phrase
+    Phrase(String),
+    Regex(Regex),
+    Exists(JsonPath),
+    Predicate(Predicate),
+}
+
+#[derive(Debug)]
+struct Predicate {
+    path: JsonPath,
+    /// Top-level field name, for looking up its unit kind
+    field: Option<String>,
+    op: Op,
+}
+
+#[derive(Debug)]
+enum Op {
+    Matches(String),
+    Equals(String),
+    Compare(Comparison, Amount),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// Right-hand side of a comparison
+#[derive(Debug)]
+enum Amount {
+    Number(f64),
+    /// The query value as written, parsed per entity once the field's unit kind is known
+    Unit(String),
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+        };
+        let expr = if tokens.is_empty() {
+            None
+        } else {
+            Some(parser.parse_or()?)
+        };
+        if parser.pos < tokens.len() {
+            return Err(QueryError::UnbalancedParentheses);
+        }
+
+        let mut text = Vec::new();
+        if let Some(expr) = &expr {
+            expr.required_words(&mut text);
+        }
+        Ok(Self {
+            expr,
+            text: text.join(" "),
+        })
+    }
+
+    /// Plain-text words every match has, lowercased. Words under `NOT` or
+    /// in one branch of an `OR` are left out, since a match needn't have them.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// JSON paths that matched if the entity matches the query
+    pub fn matches(&self, candidate: &Candidate) -> Option<Vec<String>> {
+        match &self.expr {
+            Some(expr) => expr.matches(candidate),
+            None => Some(Vec::new()),
+        }
+    }
+}
+
+impl Expr {
+    fn matches(&self, candidate: &Candidate) -> Option<Vec<String>> {
+        match self {
+            Expr::And(exprs) => {
+                let mut paths = Vec::new();
+                for expr in exprs {
+                    paths.extend(expr.matches(candidate)?);
+                }
+                Some(paths)
+            }
+            Expr::Or(exprs) => {
+                let mut matched = false;
+                let mut paths = Vec::new();
+                for expr in exprs {
+                    if let Some(found) = expr.matches(candidate) {
+                        matched = true;
+                        paths.extend(found);
+                    }
+                }
+                matched.then_some(paths)
+            }
+            Expr::Not(expr) => match expr.matches(candidate) {
+                Some(_) => None,
+                None => Some(Vec::new()),
+            },
+            Expr::Term(term) => term.matches(candidate),
+        }
+    }
+
+    /// Words and phrases the expression can't match without
+    fn required_words(&self, words: &mut Vec<String>) {
+        match self {
+            Expr::And(exprs) => {
+                for expr in exprs {
+                    expr.required_words(words);
+                }
+            }
+            Expr::Term(Term::Text(text) | Term::Phrase(text)) => words.push(text.clone()),
+            Expr::Or(_) | Expr::Not(_) | Expr::Term(_) => {}
+        }
+    }
+}
+
+impl Term {
+    fn matches(&self, candidate: &Candidate) -> Option<Vec<String>> {
+        let found = match self {
+            Term::Phrase(text) => match_name(candidate, |s| s.to_lowercase().contains(text)),
+            Term::Text(text) => match_name(candidate, |s| fuzzy_score(text, &[s]).is_some()),
+            Term::Regex(regex) => match_name(candidate, |s| regex.is_match(s)),
+            Term::Exists(path) => path
+                .select(candidate.json)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect(),
+            Term::Predicate(predicate) => predicate.matches(candidate),
+        };
+        (!found.is_empty()).then_some(found)
+    }
+}
+
+/// Paths of the ID and display name that satisfy `f`
+fn match_name(candidate: &Candidate, f: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut paths = Vec::new();
+    if f(candidate.id) {
+        paths.push("$.id".to_string());
+    }
+    if candidate.display_name.is_some_and(&f) {
+        paths.push(match candidate.json.get("name") {
+            Some(Value::Object(_)) => "$.name.str".to_string(),
+            _ => "$.name".to_string(),
+        });
+    }
+    paths
+}
+
+impl Predicate {
+    fn matches(&self, candidate: &Candidate) -> Vec<String> {
+        let mut leaves = Vec::new();
+        for (path, value) in self.path.select(candidate.json) {
+            collect_leaves(path, value, &mut leaves);
+        }
+
+        let unit_field = self.field.as_deref().and_then(|field| {
+            units::fields_for_type(candidate.entity_type).find(|f| f.field == field)
+        });
+
+        leaves
+            .into_iter()
+            .filter(|(_, value)| match &self.op {
+                Op::Matches(pattern) => {
+                    leaf_text(value).is_some_and(|text| glob_match(pattern, &text))
+                }
+                Op::Equals(expected) => leaf_text(value).is_some_and(|text| text == *expected),
+                Op::Regex(regex) => value.as_str().is_some_and(|s| regex.is_match(s)),
+                Op::Compare(comparison, amount) => {
+                    let (actual, expected) = match amount {
+                        Amount::Number(expected) => (leaf_number(value), Some(*expected)),
+                        Amount::Unit(text) => {
+                            let kind = unit_field.map(|f| f.kind).or_else(|| infer_kind(text));
+                            match kind {
+                                Some(kind) => (
+                                    leaf_amount(value, kind, unit_field.map(|f| f.legacy)),
+                                    units::parse(text, kind).ok().map(|v| v as f64),
+                                ),
+                                None => (None, None),
+                            }
+                        }
+                    };
+                    match (actual, expected) {
+                        (Some(actual), Some(expected)) => comparison.holds(actual, expected),
+                        _ => false,
+                    }
+                }
+            })
+            .map(|(path, _)| path)
+            .collect()
+    }
+}
+
+impl Comparison {
+    fn holds(self, actual: f64, expected: f64) -> bool {
+        match self {
+            Comparison::Less => actual < expected,
+            Comparison::LessOrEqual => actual <= expected,
+            Comparison::Greater => actual > expected,
+            Comparison::GreaterOrEqual => actual >= expected,
+        }
+    }
+}
+
+/// Every scalar under a value, with its path
+fn collect_leaves<'v>(path: String, value: &'v Value, out: &mut Vec<(String, &'v Value)>) {
+    match value {
+        Value::Object(obj) => {
+            for (key, child) in obj {
+                collect_leaves(child_path(&path, key), child, out);
+            }
+        }
+        Value::Array(arr) => {
+            for (i, child) in arr.iter().enumerate() {
+                collect_leaves(format!("{}[{}]", path, i), child, out);
+            }
+        }
+        _ => out.push((path, value)),
+    }
+}
+
+fn leaf_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn leaf_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// A leaf as an amount in base units: unit strings are parsed, and legacy
+/// integers are scaled the way the game reads them
+fn leaf_amount(value: &Value, kind: UnitKind, legacy: Option<LegacyFormat>) -> Option<f64> {
+    match value {
+        Value::String(s) => units::parse(s, kind).ok().map(|v| v as f64),
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            Some(match legacy {
+                Some(LegacyFormat::Scale(scale)) => n * scale as f64,
+                Some(LegacyFormat::Moves) => n / 100.0,
+                _ => n,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn infer_kind(text: &str) -> Option<UnitKind> {
+    INFERRED_KINDS
+        .iter()
+        .copied()
+        .find(|kind| units::parse(text, *kind).is_ok())
+}
+
+/// Case-insensitive match where `*` matches any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let mut rest = text.as_str();
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            let Some(after) = rest.strip_prefix(part) else {
+                return false;
+            };
+            rest = after;
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            let Some(found) = rest.find(part) else {
+                return false;
+            };
+            rest = &rest[found + part.len()..];
+        }
+    }
+    true
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    /// A term, with quotes removed
+    Word(String),
+    /// A term that was quoted as a whole, with the quotes removed
+    Phrase(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut word = String::new();
+                // The term as typed, quotes included
+                let mut raw = String::new();
+                let mut quoted = false;
+                let mut brackets = 0usize;
+                while let Some(&c) = chars.peek() {
+                    match c {
+                        // Quotes inside brackets belong to the JSONPath
+                        '"' if brackets > 0 => {
+                            quoted = !quoted;
+                            word.push(c);
+                        }
+                        '"' => quoted = !quoted,
+                        '[' if !quoted => {
+                            brackets += 1;
+                            word.push(c);
+                        }
+                        ']' if !quoted => {
+                            brackets = brackets.saturating_sub(1);
+                            word.push(c);
+                        }
+                        c if !quoted
+                            && brackets == 0
+                            && (c.is_whitespace() || c == '(' || c == ')') =>
+                        {
+                            break
+                        }
+                        c => word.push(c),
+                    }
+                    raw.push(c);
+                    chars.next();
+                }
+                if quoted {
+                    return Err(QueryError::UnterminatedQuote);
+                }
+                let term = |raw: &str, word: &str| {
+                    let phrase = raw.len() >= 2
+                        && raw.starts_with('"')
+                        && raw.ends_with('"')
+                        && !raw[1..raw.len() - 1].contains('"');
+                    if phrase {
+                        Token::Phrase(word.to_string())
+                    } else {
+                        Token::Word(word.to_string())
+                    }
+                };
+                match word.as_str() {
+                    "AND" if raw == word => tokens.push(Token::And),
+                    "OR" if raw == word => tokens.push(Token::Or),
+                    "NOT" if raw == word => tokens.push(Token::Not),
+                    _ => match (word.strip_prefix('-'), raw.strip_prefix('-')) {
+                        (Some(rest), Some(raw_rest)) if !rest.is_empty() => {
+                            tokens.push(Token::Not);
+                            tokens.push(term(raw_rest, rest));
+                        }
+                        _ => tokens.push(term(&raw, &word)),
+                    },
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'t> {
+    tokens: &'t [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            exprs.push(self.parse_and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut exprs = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.pos += 1,
+                Some(Token::Or) | Some(Token::Close) | None => break,
+                _ => {}
+            }
+            exprs.push(self.parse_unary()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        match self.peek().cloned() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::Open) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(QueryError::UnbalancedParentheses);
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(Token::Word(word)) => {
+                self.pos += 1;
+                Ok(Expr::Term(parse_term(&word)?))
+            }
+            Some(Token::Phrase(phrase)) => {
+                self.pos += 1;
+                Ok(Expr::Term(Term::Phrase(phrase.to_lowercase())))
+            }
+            Some(Token::Close) => Err(QueryError::UnbalancedParentheses),
+            Some(Token::And) | Some(Token::Or) | None => Err(QueryError::MissingTerm),
+        }
+    }
+}
+
+fn parse_term(word: &str) -> Result<Term, QueryError> {
+    if word.len() >= 2 && word.starts_with('/') && word.ends_with('/') {
+        let regex = Regex::new(&word[1..word.len() - 1])
+            .map_err(|e| QueryError::InvalidRegex(e.to_string()))?;
+        return Ok(Term::Regex(regex));
+    }
+
+    let Some((field, op, value)) = split_predicate(word) else {
+        if word.starts_with('$') {
+            return Ok(Term::Exists(JsonPath::parse(word)?));
+        }
+        return Ok(Term::Text(word.to_lowercase()));
+    };
+
+    if value.is_empty() && matches!(op, "<" | "<=" | ">" | ">=") {
+        return Err(QueryError::MissingValue(field.to_string()));
+    }
+
+    let (path, field) = if field.starts_with('$') {
+        (JsonPath::parse(field)?, None)
+    } else {
+        let top = field.split(['.', '[']).next().unwrap_or(field);
+        (
+            JsonPath::parse(&format!("$.{}", field))?,
+            Some(top.to_string()),
+        )
+    };
+
+    let amount = || match value.trim().parse::<f64>() {
+        Ok(n) => Amount::Number(n),
+        Err(_) => Amount::Unit(value.to_string()),
+    };
+    let op = match op {
+        ":" => Op::Matches(value.to_string()),
+        "=" => Op::Equals(value.to_string()),
+        "~" => Op::Regex(Regex::new(value).map_err(|e| QueryError::InvalidRegex(e.to_string()))?),
+        "<" => Op::Compare(Comparison::Less, amount()),
+        "<=" => Op::Compare(Comparison::LessOrEqual, amount()),
+        ">" => Op::Compare(Comparison::Greater, amount()),
+        _ => Op::Compare(Comparison::GreaterOrEqual, amount()),
+    };
+
+    Ok(Term::Predicate(Predicate { path, field, op }))
+}
+
+/// Split `field<op>value` at the first operator outside brackets
+fn split_predicate(word: &str) -> Option<(&str, &str, &str)> {
+    let mut brackets = 0usize;
+    for (i, c) in word.char_indices() {
+        match c {
+            '[' => brackets += 1,
+            ']' => brackets = brackets.saturating_sub(1),
+            ':' | '=' | '~' | '<' | '>' if brackets == 0 && i > 0 => {
+                let op_len = if matches!(c, '<' | '>') && word[i + 1..].starts_with('=') {
+                    2
+                } else {
+                    1
+                };
+                return Some((&word[..i], &word[i..i + op_len], &word[i + op_len..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QueryError {
+    #[error("unbalanced parentheses")]
+    UnbalancedParentheses,
+
+    #[error("unterminated quote")]
+    UnterminatedQuote,
+
+    #[error("expected a search term")]
+    MissingTerm,
+
+    #[error("no value to compare {0} with")]
+    MissingValue(String),
+
+    #[error("invalid regex: {0}")]
+    InvalidRegex(String),
+
+    #[error(transparent)]
+    InvalidPath(#[from] JsonPathError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn matches(query: &str, json: &Value) -> bool {
+        let candidate = Candidate {
+            id: json["id"].as_str().unwrap(),
+            entity_type: json["type"].as_str().unwrap(),
+            display_name: json["name"].as_str(),
+            json,
+        };
+        Query::parse(query).unwrap().matches(&candidate).is_some()
+    }
+
+    #[test]
+    fn words_are_fuzzy_and_phrases_literal() {
+        let rock = json!({ "type": "GENERIC", "id": "rock", "name": "rock" });
+        let brick = json!({ "type": "GENERIC", "id": "brick", "name": "brick" });
+
+        assert!(matches("rck", &rock));
+        assert!(!matches("\"rck\"", &rock));
+        assert!(matches("\"rock\"", &rock));
+        assert!(!matches("\"rock\"", &brick));
+        assert!(matches("-\"rock\"", &brick));
+    }
+
+    #[test]
+    fn only_required_words_rank() {
+        let text = |query: &str| Query::parse(query).unwrap().text().to_string();
+
+        assert_eq!(
+            text("zombie \"big brute\" type:MONSTER"),
+            "zombie big brute"
+        );
+        assert_eq!(text("zombie -brute"), "zombie");
+        assert_eq!(text("zombie NOT (brute OR soldier)"), "zombie");
+        assert_eq!(text("zombie OR brute"), "");
+        assert_eq!(text("(zombie OR brute) hulk"), "hulk");
+    }
+
+    #[test]
+    fn predicates_and_units() {
+        let item = json!({
+            "type": "TOOL",
+            "id": "hammer",
+            "weight": "1 kg",
+            "qualities": [["HAMMER", 3]],
+            "flags": ["BELT_CLIP"]
+        });
+
+        assert!(matches("type:tool flags:BELT_CLIP", &item));
+        assert!(matches("weight>500g", &item));
+        assert!(!matches("weight>2kg", &item));
+        assert!(matches("$.qualities[?(@[0] == 'HAMMER')]", &item));
+        assert!(matches("type:ARMOR OR -flags:WATERPROOF", &item));
+    }
+
+    #[test]
+    fn query_errors() {
+        assert!(matches!(
+            Query::parse("(type:TOOL"),
+            Err(QueryError::UnbalancedParentheses)
+        ));
+        assert!(matches!(
+            Query::parse("\"rock"),
+            Err(QueryError::UnterminatedQuote)
+        ));
+    }
+}
@@ -0,0 +1,508 @@
+use serde_json::Value;
+
+use super::fixes::pointer_from_path;
+use super::ids::id_namespace;
+use super::jsonpath::child_path;
+use super::validator::ITEM_TYPES;
+use crate::models::SYMBOL_SECTIONS;
+
+const RECIPE_TYPES: &[&str] = &["recipe", "uncraft"];
+
+/// How IDs are written at a reference site
+#[derive(Debug, Clone, Copy)]
+enum Form {
+    /// A string ID, a list of them, weighted `[id, weight]` pairs, or a
+    /// `{"param", "fallback"}` / `{"distribution"}` choice
+    Id,
+    /// The keys of an object
+    Keys,
+    /// Recipe requirement lists: alternatives of `[id, amount]`, where a
+    /// trailing `"LIST"` names a requirement instead of an item
+    Requirements,
+    /// Item group entries: `"id"` or `[id, probability]` items, or objects
+    /// with `item` / `group` and nested `entries`
+    GroupEntries,
+    /// An item group ID, or item group entries written inline
+    Group,
+}
+
+/// Fields that refer to other entities, by entity type: dotted path (`*`
+/// matches any key, arrays along the way are entered), how the IDs are
+/// written and their ID namespace
+const ENTITY_SITES: &[(&[&str], &str, Form, &str)] = &[
+    (RECIPE_TYPES, "result", Form::Id, "item"),
+    (RECIPE_TYPES, "components", Form::Requirements, "item"),
+    (RECIPE_TYPES, "tools", Form::Requirements, "item"),
+    (RECIPE_TYPES, "byproducts", Form::Id, "item"),
+    (RECIPE_TYPES, "using", Form::Id, "requirement"),
+    (RECIPE_TYPES, "book_learn", Form::Id, "item"),
+    (RECIPE_TYPES, "book_learn", Form::Keys, "item"),
+    (&["requirement"], "components", Form::Requirements, "item"),
+    (&["requirement"], "tools", Form::Requirements, "item"),
+    (&["item_group"], "items", Form::GroupEntries, "item"),
+    (&["item_group"], "entries", Form::GroupEntries, "item"),
+    (&["item_group"], "container-item", Form::Id, "item"),
+    (&["monstergroup"], "default", Form::Id, "MONSTER"),
+    (&["monstergroup"], "monsters.monster", Form::Id, "MONSTER"),
+    (&["MONSTER"], "death_drops", Form::Group, "item_group"),
+    (&["MONSTER"], "upgrades.into", Form::Id, "MONSTER"),
+    (
+        &["MONSTER"],
+        "upgrades.into_group",
+        Form::Id,
+        "monstergroup",
+    ),
+    (
+        &["MONSTER"],
+        "reproduction.baby_monster",
+        Form::Id,
+        "MONSTER",
+    ),
+    (&["MONSTER"], "reproduction.baby_egg", Form::Id, "item"),
+    (&["MONSTER"], "harvest", Form::Id, "harvest"),
+    (&["harvest"], "entries.drop", Form::Id, "item"),
+    (&["vehicle_group"], "vehicles", Form::Id, "vehicle"),
+    (&["terrain"], "open", Form::Id, "terrain"),
+    (&["terrain"], "close", Form::Id, "terrain"),
+    (&["terrain"], "transforms_into", Form::Id, "terrain"),
+    (&["terrain"], "bash.ter_set", Form::Id, "terrain"),
+    (&["terrain"], "deconstruct.ter_set", Form::Id, "terrain"),
+    (&["furniture"], "open", Form::Id, "furniture"),
+    (&["furniture"], "close", Form::Id, "furniture"),
+    (&["furniture"], "transforms_into", Form::Id, "furniture"),
+    (&["furniture"], "bash.furn_set", Form::Id, "furniture"),
+    (
+        &["furniture"],
+        "deconstruct.furn_set",
+        Form::Id,
+        "furniture",
+    ),
+    (
+        &["terrain", "furniture"],
+        "bash.items",
+        Form::Group,
+        "item_group",
+    ),
+    (
+        &["terrain", "furniture"],
+        "deconstruct.items",
+        Form::Group,
+        "item_group",
+    ),
+    (ITEM_TYPES, "revert_to", Form::Id, "item"),
+    (&["mapgen"], "om_terrain", Form::Id, "overmap_terrain"),
+];
+
+/// `place_*` fields that don't match their symbol section's fields
+const PLACE_FIELDS: &[(&str, &str, &str)] = &[
+    ("place_terrain", "ter", "terrain"),
+    ("place_furniture", "furn", "furniture"),
+    ("place_loot", "item", "item"),
+    ("place_loot", "group", "item_group"),
+    ("place_monster", "group", "monstergroup"),
+];
+
+/// Where an entity's JSON mentions another entity's ID
+#[derive(Debug, Clone)]
+pub struct Reference {
+    /// JSON path of the value, or of the member whose key is the ID
+    pub path: String,
+    /// The ID is an object key (e.g. `"book_learn": { "id": 3 }`)
+    pub is_key: bool,
+    /// The referenced ID
+    pub id: String,
+}
+
+/// Find the places an entity refers to `id` of the given ID namespace.
+///
+/// Only fields known to refer to that namespace count: `copy-from`, recipe
+/// results, components and tools, item group entries, monster groups, and
+/// the symbol sections, `place_*` entries and palettes of mapgen and
+/// palettes. Other fields that happen to hold the same string (materials,
+/// ammo types, flags, text) are left alone.
+pub fn find_references(json: &Value, namespace: &str, id: &str) -> Vec<Reference> {
+    find_matching(json, |ref_namespace, value| {
+        value == id && ref_namespace == namespace
+    })
+}
+
+/// Like [`find_references`], for any namespace and ID `is_target` accepts
+pub fn find_matching(json: &Value, is_target: impl Fn(&str, &str) -> bool) -> Vec<Reference> {
+    let mut finder = Finder {
+        is_target: &is_target,
+        found: Vec::new(),
+    };
+    let Some(obj) = json.as_object() else {
+        return finder.found;
+    };
+    let entity_type = obj.get("type").and_then(|v| v.as_str()).unwrap_or("");
+
+    // copy-from always names a definition of the entity's own kind
+    if let Some(Value::String(parent)) = obj.get("copy-from") {
+        finder.push(
+            parent,
+            child_path("$", "copy-from"),
+            false,
+            id_namespace(entity_type),
+        );
+    }
+
+    for (types, path, form, namespace) in ENTITY_SITES {
+        if types.contains(&entity_type) {
+            finder.visit(json, &split(path), "$".to_string(), *form, namespace);
+        }
+    }
+
+    match entity_type {
+        "mapgen" => {
+            if let Some(body) = obj.get("object") {
+                finder.visit_map(body, "$.object");
+            }
+        }
+        "palette" => finder.visit_map(json, "$"),
+        _ => {}
+    }
+
+    finder.found
+}
+
+fn split(path: &str) -> Vec<&str> {
+    path.split('.').collect()
+}
+
+struct Finder<'f, F: Fn(&str, &str) -> bool> {
+    is_target: &'f F,
+    found: Vec<Reference>,
+}
+
+impl<F: Fn(&str, &str) -> bool> Finder<'_, F> {
+    /// References in a mapgen `object` or a palette
+    fn visit_map(&mut self, body: &Value, path: &str) {
+        let Some(obj) = body.as_object() else {
+            return;
+        };
+
+        if let Some(fill_ter) = obj.get("fill_ter") {
+            self.read(fill_ter, child_path(path, "fill_ter"), Form::Id, "terrain");
+        }
+        if let Some(palettes) = obj.get("palettes") {
+            self.read(palettes, child_path(path, "palettes"), Form::Id, "palette");
+        }
+
+        for section in SYMBOL_SECTIONS {
+            let section_path = child_path(path, section.name);
+            if let Some(Value::Object(entries)) = obj.get(section.name) {
+                for (symbol, entry) in entries {
+                    let entry_path = child_path(&section_path, symbol);
+                    if let Some(namespace) = section.namespace {
+                        self.read(entry, entry_path.clone(), Form::Id, namespace);
+                    }
+                    for (field, namespace) in section.fields {
+                        self.visit(
+                            entry,
+                            &split(field),
+                            entry_path.clone(),
+                            Form::Id,
+                            namespace,
+                        );
+                    }
+                }
+            }
+
+            // place_items etc. name their content the same way as the section
+            let place = format!("place_{}", section.name);
+            if let Some(entries) = obj.get(&place) {
+                for (field, namespace) in section.fields {
+                    self.visit(
+                        entries,
+                        &split(field),
+                        child_path(path, &place),
+                        Form::Id,
+                        namespace,
+                    );
+                }
+            }
+        }
+        for (place, field, namespace) in PLACE_FIELDS {
+            if let Some(entries) = obj.get(*place) {
+                self.visit(
+                    entries,
+                    &[field],
+                    child_path(path, place),
+                    Form::Id,
+                    namespace,
+                );
+            }
+        }
+    }
+
+    /// Follow `segments` from `value`, entering arrays along the way, and
+    /// read what's at the end in the given form
+    fn visit(
+        &mut self,
+        value: &Value,
+        segments: &[&str],
+        path: String,
+        form: Form,
+        namespace: &str,
+    ) {
+        let Some((segment, rest)) = segments.split_first() else {
+            self.read(value, path, form, namespace);
+            return;
+        };
+        match value {
+            Value::Array(arr) => {
+                for (i, item) in arr.iter().enumerate() {
+                    self.visit(item, segments, format!("{}[{}]", path, i), form, namespace);
+                }
+            }
+            Value::Object(obj) if *segment == "*" => {
+                for (key, item) in obj {
+                    self.visit(item, rest, child_path(&path, key), form, namespace);
+                }
+            }
+            Value::Object(obj) => {
+                if let Some(item) = obj.get(*segment) {
+                    self.visit(item, rest, child_path(&path, segment), form, namespace);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, value: &Value, path: String, form: Form, namespace: &str) {
+        match (form, value) {
+            (_, Value::String(id)) => {
+                let namespace = match form {
+                    Form::Group => "item_group",
+                    _ => namespace,
+                };
+                self.push(id, path, false, namespace);
+            }
+            (Form::Keys, Value::Object(obj)) => {
+                for key in obj.keys() {
+                    self.push(key, child_path(&path, key), true, namespace);
+                }
+            }
+            (Form::Id, Value::Array(entries)) => {
+                for (i, entry) in entries.iter().enumerate() {
+                    let entry_path = format!("{}[{}]", path, i);
+                    match entry.as_array().map(Vec::as_slice) {
+                        // Weighted [id, weight]
+                        Some([Value::String(id), Value::Number(_)]) => {
+                            self.push(id, format!("{}[0]", entry_path), false, namespace)
+                        }
+                        _ => self.read(entry, entry_path, form, namespace),
+                    }
+                }
+            }
+            (Form::Id, Value::Object(obj)) => {
+                for field in ["fallback", "distribution"] {
+                    if let Some(choice) = obj.get(field) {
+                        self.read(choice, child_path(&path, field), form, namespace);
+                    }
+                }
+            }
+            (Form::Requirements, Value::Array(entries)) => {
+                match entries.as_slice() {
+                    // [id, amount] or [requirement, amount, "LIST"]
+                    [Value::String(id), rest @ ..] => {
+                        let is_list = rest.iter().any(|v| v.as_str() == Some("LIST"));
+                        let namespace = if is_list { "requirement" } else { namespace };
+                        self.push(id, format!("{}[0]", path), false, namespace);
+                    }
+                    _ => {
+                        for (i, entry) in entries.iter().enumerate() {
+                            self.read(entry, format!("{}[{}]", path, i), form, namespace);
+                        }
+                    }
+                }
+            }
+            (Form::GroupEntries | Form::Group, Value::Array(entries)) => {
+                for (i, entry) in entries.iter().enumerate() {
+                    let entry_path = format!("{}[{}]", path, i);
+                    match entry {
+                        // An item by ID, or [id, probability]
+                        Value::String(id) => self.push(id, entry_path, false, "item"),
+                        Value::Array(pair) => {
+                            if let Some(Value::String(id)) = pair.first() {
+                                self.push(id, format!("{}[0]", entry_path), false, "item");
+                            }
+                        }
+                        _ => self.read(entry, entry_path, Form::GroupEntries, namespace),
+                    }
+                }
+            }
+            (Form::GroupEntries | Form::Group, Value::Object(obj)) => {
+                for (field, namespace) in [
+                    ("item", "item"),
+                    ("group", "item_group"),
+                    ("container-item", "item"),
+                ] {
+                    if let Some(Value::String(id)) = obj.get(field) {
+                        self.push(id, child_path(&path, field), false, namespace);
+                    }
+                }
+                for field in ["entries", "items", "distribution", "collection"] {
+                    if let Some(nested) = obj.get(field) {
+                        self.read(nested, child_path(&path, field), Form::GroupEntries, "item");
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push(&mut self, id: &str, path: String, is_key: bool, namespace: &str) {
+        if (self.is_target)(namespace, id) {
+            self.found.push(Reference {
+                path,
+                is_key,
+                id: id.to_string(),
+            });
+        }
+    }
+}
+
+/// Point the given references at `new_id`. Returns how many were rewritten.
+pub fn rewrite_references(json: &mut Value, references: &[Reference], new_id: &str) -> usize {
+    // Rewrite keys last, innermost first, so earlier paths stay valid
+    let values = references.iter().filter(|r| !r.is_key);
+    let keys = references.iter().filter(|r| r.is_key).rev();
+
+    let mut rewritten = 0;
+    for reference in values.chain(keys) {
+        let Some(pointer) = pointer_from_path(&reference.path) else {
+            continue;
+        };
+
+        if reference.is_key {
+            let Some((parent, key)) = pointer.rsplit_once('/') else {
+                continue;
+            };
+            let key = key.replace("~1", "/").replace("~0", "~");
+            let Some(Value::Object(obj)) = json.pointer_mut(parent) else {
+                continue;
+            };
+            if let Some(value) = obj.remove(&key) {
+                obj.insert(new_id.to_string(), value);
+                rewritten += 1;
+            }
+        } else if let Some(value) = json.pointer_mut(&pointer) {
+            *value = Value::String(new_id.to_string());
+            rewritten += 1;
+        }
+    }
+    rewritten
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(json: &Value, namespace: &str, id: &str) -> Vec<String> {
+        find_references(json, namespace, id)
+            .into_iter()
+            .map(|r| r.path)
+            .collect()
+    }
+
+    #[test]
+    fn recipe_components_and_tools() {
+        let recipe = json!({
+            "type": "recipe",
+            "result": "leather_armor",
+            "components": [[["leather", 4], ["fur", 4]], [["sewing_kit_parts", 1, "LIST"]]],
+            "tools": [[["needle_bone", -1]]]
+        });
+        assert_eq!(paths(&recipe, "item", "leather"), ["$.components[0][0][0]"]);
+        assert_eq!(
+            paths(&recipe, "requirement", "sewing_kit_parts"),
+            ["$.components[1][0][0]"]
+        );
+        assert_eq!(paths(&recipe, "item", "needle_bone"), ["$.tools[0][0][0]"]);
+    }
+
+    #[test]
+    fn other_kinds_of_ids_are_ignored() {
+        let armor = json!({
+            "type": "ARMOR",
+            "id": "leather_vest",
+            "material": ["leather"],
+            "armor": [{ "material": [{ "type": "leather" }] }],
+            "pocket_data": [{ "ammo_restriction": { "battery": 100 } }]
+        });
+        assert!(paths(&armor, "item", "leather").is_empty());
+        assert!(paths(&armor, "item", "battery").is_empty());
+    }
+
+    #[test]
+    fn item_groups() {
+        let group = json!({
+            "type": "item_group",
+            "id": "trash",
+            "subtype": "distribution",
+            "items": ["can", ["bottle", 10], { "group": "junk", "prob": 5 }, { "item": "rag" }]
+        });
+        assert_eq!(paths(&group, "item", "bottle"), ["$.items[1][0]"]);
+        assert_eq!(paths(&group, "item", "rag"), ["$.items[3].item"]);
+        assert_eq!(paths(&group, "item_group", "junk"), ["$.items[2].group"]);
+        assert!(paths(&group, "item_group", "can").is_empty());
+    }
+
+    #[test]
+    fn mapgen_sections_and_placements() {
+        let mapgen = json!({
+            "type": "mapgen",
+            "om_terrain": "house",
+            "object": {
+                "fill_ter": "t_floor",
+                "palettes": ["house_palette"],
+                "terrain": { ".": "t_floor", "#": [["t_wall", 2], "t_floor"] },
+                "items": { "x": { "item": "trash", "chance": 5 } },
+                "place_items": [{ "item": "trash", "x": 1, "y": 1 }],
+                "place_loot": [{ "item": "trash", "x": 2, "y": 2 }]
+            }
+        });
+        assert_eq!(
+            paths(&mapgen, "terrain", "t_floor"),
+            [
+                "$.object.fill_ter",
+                "$.object.terrain[\"#\"][1]",
+                "$.object.terrain[\".\"]"
+            ]
+        );
+        assert_eq!(
+            paths(&mapgen, "item_group", "trash"),
+            ["$.object.items.x.item", "$.object.place_items[0].item"]
+        );
+        assert_eq!(
+            paths(&mapgen, "item", "trash"),
+            ["$.object.place_loot[0].item"]
+        );
+        assert_eq!(
+            paths(&mapgen, "palette", "house_palette"),
+            ["$.object.palettes[0]"]
+        );
+    }
+
+    #[test]
+    fn copy_from_matches_the_entity_namespace() {
+        let gun = json!({ "type": "GUN", "id": "rifle2", "copy-from": "rifle" });
+        assert_eq!(paths(&gun, "item", "rifle"), ["$.copy-from"]);
+        assert!(paths(&gun, "MONSTER", "rifle").is_empty());
+    }
+
+    #[test]
+    fn rewrite() {
+        let mut recipe = json!({
+            "type": "recipe",
+            "result": "rope",
+            "book_learn": { "manual": 2 }
+        });
+        let refs = find_references(&recipe, "item", "manual");
+        assert_eq!(rewrite_references(&mut recipe, &refs, "manual_2"), 1);
+        assert_eq!(recipe["book_learn"], json!({ "manual_2": 2 }));
+    }
+}
@@ -0,0 +1,208 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::history::Journal;
+use crate::models::{ContentPack, EntityKey, EntityMeta};
+
+/// A file path inside a pack, relative to the pack root.
+/// Absolute paths under the pack are accepted; anything leaving the pack is not.
+pub fn pack_relative_path(pack: &ContentPack, path: &Path) -> Result<PathBuf, ReorganizeError> {
+    let relative = path.strip_prefix(&pack.path).unwrap_or(path);
+    let valid = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !valid || relative.as_os_str().is_empty() {
+        return Err(ReorganizeError::InvalidPath(path.to_path_buf()));
+    }
+    if relative.extension().and_then(|e| e.to_str()) != Some("json") {
+        return Err(ReorganizeError::NotJson(path.to_path_buf()));
+    }
+    Ok(relative
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect())
+}
+
+/// Keys of the entities in a source file, in file order
+pub fn file_entities(pack: &ContentPack, file: &Path) -> Vec<EntityKey> {
+    let mut entities: Vec<(&EntityKey, usize)> = pack
+        .entities
+        .iter()
+        .filter(|(_, entity)| entity.source_file == file)
+        .map(|(key, entity)| (key, entity.array_index))
+        .collect();
+    entities.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+    entities.into_iter().map(|(key, _)| key.clone()).collect()
+}
+
+/// Move entities to the end of a file, keeping their order: by source file,
+/// then by position within it. Entities already in the file stay put.
+/// Returns the keys of the entities that moved.
+pub fn move_entities(
+    journal: &mut Journal,
+    pack: &mut ContentPack,
+    keys: &[EntityKey],
+    target: &Path,
+) -> Result<Vec<EntityKey>, ReorganizeError> {
+    check_target(pack, target)?;
+
+    let mut order = Vec::new();
+    for key in keys {
+        let entity = pack
+            .entities
+            .get(key)
+            .ok_or_else(|| ReorganizeError::EntityNotFound(key.clone()))?;
+        if entity.source_file != target && !order.iter().any(|(k, _, _)| k == key) {
+            order.push((key.clone(), entity.source_file.clone(), entity.array_index));
+        }
+    }
+    order.sort_by(|a, b| a.1.cmp(&b.1).then(a.2.cmp(&b.2)));
+
+    let mut moved = Vec::new();
+    for (key, _, _) in order {
+        journal
+            .move_entity(pack, &key, target.to_path_buf())
+            .map_err(|_| ReorganizeError::EntityNotFound(key.clone()))?;
+        moved.push(key);
+    }
+    Ok(moved)
+}
+
+/// Move each type of entity in a file to its own file next to it, named
+/// `{stem}_{type}.json`. Types are taken in order of first appearance.
+/// Returns the files created.
+pub fn split_by_type(
+    journal: &mut Journal,
+    pack: &mut ContentPack,
+    file: &Path,
+) -> Result<Vec<PathBuf>, ReorganizeError> {
+    let keys = file_entities(pack, file);
+    if keys.is_empty() {
+        return Err(ReorganizeError::NoEntities(file.to_path_buf()));
+    }
+
+    let mut types: Vec<(String, Vec<EntityKey>)> = Vec::new();
+    for key in keys {
+        let entity_type = pack.entities[&key].meta.entity_type.clone();
+        match types.iter_mut().find(|(t, _)| *t == entity_type) {
+            Some((_, keys)) => keys.push(key),
+            None => types.push((entity_type, vec![key])),
+        }
+    }
+    if types.len() < 2 {
+        return Err(ReorganizeError::SingleType(file.to_path_buf()));
+    }
+
+    let stem = file
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("entities");
+    let targets: Vec<PathBuf> = types
+        .iter()
+        .map(|(entity_type, _)| {
+            file.with_file_name(format!("{}_{}.json", stem, entity_type.to_lowercase()))
+        })
+        .collect();
+
+    // Splitting into an existing file would mix in unrelated entities
+    for target in &targets {
+        let in_use = pack.entities.values().any(|e| e.source_file == *target);
+        if in_use || pack.path.join(target).exists() {
+            return Err(ReorganizeError::TargetExists(target.clone()));
+        }
+    }
+
+    for ((_, keys), target) in types.iter().zip(&targets) {
+        move_entities(journal, pack, keys, target)?;
+    }
+    Ok(targets)
+}
+
+/// Move every entity in `files` into `target`, file by file in the order
+/// given. If the target is one of the files, its entities stay first.
+/// Returns the keys of the entities that moved.
+pub fn merge_files(
+    journal: &mut Journal,
+    pack: &mut ContentPack,
+    files: &[PathBuf],
+    target: &Path,
+) -> Result<Vec<EntityKey>, ReorganizeError> {
+    check_target(pack, target)?;
+
+    let mut sources = Vec::new();
+    for file in files.iter().filter(|file| *file != target) {
+        let keys = file_entities(pack, file);
+        if keys.is_empty() {
+            return Err(ReorganizeError::NoEntities(file.clone()));
+        }
+        sources.push(keys);
+    }
+
+    let mut moved = Vec::new();
+    for keys in sources {
+        // One file at a time so each file's entities follow the previous file's
+        moved.extend(move_entities(journal, pack, &keys, target)?);
+    }
+    Ok(moved)
+}
+
+/// Entities can only be moved into a file the pack owns: one it loaded
+/// entities from, a new file, or an existing file with no entities in it.
+/// An existing file with entities the pack didn't load (for example in an
+/// excluded directory) would lose them when saved.
+pub fn check_target(pack: &ContentPack, target: &Path) -> Result<(), ReorganizeError> {
+    let known = pack.dirty_files.iter().any(|file| file == target)
+        || pack.entities.values().any(|e| e.source_file == target);
+    let full_path = pack.path.join(target);
+    if known || !full_path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&full_path).map_err(|e| ReorganizeError::IoError {
+        path: full_path.clone(),
+        message: e.to_string(),
+    })?;
+    let json: Value = serde_json::from_str(&content)
+        .map_err(|_| ReorganizeError::NotArray(target.to_path_buf()))?;
+    let array = json
+        .as_array()
+        .ok_or_else(|| ReorganizeError::NotArray(target.to_path_buf()))?;
+    if array
+        .iter()
+        .any(|value| EntityMeta::from_json(value).is_some())
+    {
+        return Err(ReorganizeError::UnloadedEntities(target.to_path_buf()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReorganizeError {
+    #[error("Entity {0} not found")]
+    EntityNotFound(EntityKey),
+
+    #[error("{0} is not a path inside the pack")]
+    InvalidPath(PathBuf),
+
+    #[error("{0} is not a .json file")]
+    NotJson(PathBuf),
+
+    #[error("{0} is not a JSON array")]
+    NotArray(PathBuf),
+
+    #[error("{0} has entities that weren't loaded with the pack")]
+    UnloadedEntities(PathBuf),
+
+    #[error("{0} already exists")]
+    TargetExists(PathBuf),
+
+    #[error("{0} has no entities")]
+    NoEntities(PathBuf),
+
+    #[error("{0} only has one type of entity")]
+    SingleType(PathBuf),
+
+    #[error("IO error reading {path}: {message}")]
+    IoError { path: PathBuf, message: String },
+}
@@ -0,0 +1,120 @@
+use regex::{NoExpand, Regex, RegexBuilder};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use super::fixes::pointer_from_path;
+use super::jsonpath::{child_path, JsonPath, JsonPathError};
+use crate::models::{ProposedChange, ReplaceQuery};
+
+/// A find-and-replace query with its pattern and path compiled
+#[derive(Debug)]
+pub struct Replacer<'a> {
+    query: &'a ReplaceQuery,
+    pattern: Regex,
+    path: Option<JsonPath>,
+}
+
+impl<'a> Replacer<'a> {
+    pub fn new(query: &'a ReplaceQuery) -> Result<Self, ReplaceError> {
+        if query.find.is_empty() {
+            return Err(ReplaceError::EmptyFind);
+        }
+
+        let pattern = if query.regex {
+            query.find.clone()
+        } else {
+            regex::escape(&query.find)
+        };
+        let pattern = RegexBuilder::new(&pattern)
+            .case_insensitive(!query.case_sensitive)
+            .build()
+            .map_err(|e| ReplaceError::InvalidRegex(e.to_string()))?;
+        let path = query.path.as_deref().map(JsonPath::parse).transpose()?;
+
+        Ok(Self {
+            query,
+            pattern,
+            path,
+        })
+    }
+
+    /// Every string value in the entity that the query would change
+    pub fn changes(&self, json: &Value) -> Vec<ProposedChange> {
+        let roots = match &self.path {
+            Some(path) => path.select(json),
+            None => vec![("$".to_string(), json)],
+        };
+
+        let mut changes = Vec::new();
+        for (path, value) in roots {
+            self.collect(path, value, &mut changes);
+        }
+        // Overlapping JSONPath selections can reach a value twice
+        let mut seen = HashSet::new();
+        changes.retain(|change| seen.insert(change.path.clone()));
+        changes
+    }
+
+    fn collect(&self, path: String, value: &Value, changes: &mut Vec<ProposedChange>) {
+        match value {
+            Value::String(before) => {
+                if !self.pattern.is_match(before) {
+                    return;
+                }
+                let after = if self.query.regex {
+                    self.pattern
+                        .replace_all(before, self.query.replace.as_str())
+                } else {
+                    self.pattern
+                        .replace_all(before, NoExpand(&self.query.replace))
+                };
+                if after != *before {
+                    changes.push(ProposedChange {
+                        path,
+                        before: before.clone(),
+                        after: after.into_owned(),
+                    });
+                }
+            }
+            Value::Array(arr) => {
+                for (i, item) in arr.iter().enumerate() {
+                    self.collect(format!("{}[{}]", path, i), item, changes);
+                }
+            }
+            Value::Object(obj) => {
+                for (key, item) in obj {
+                    self.collect(child_path(&path, key), item, changes);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write proposed changes into an entity's JSON. Changes whose value no
+/// longer reads as `before` are skipped; returns how many were applied.
+pub fn apply_changes(json: &mut Value, changes: &[ProposedChange]) -> usize {
+    let mut applied = 0;
+    for change in changes {
+        let target = pointer_from_path(&change.path).and_then(|pointer| json.pointer_mut(&pointer));
+        if let Some(value) = target {
+            if value.as_str() == Some(change.before.as_str()) {
+                *value = Value::String(change.after.clone());
+                applied += 1;
+            }
+        }
+    }
+    applied
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplaceError {
+    #[error("Nothing to find")]
+    EmptyFind,
+
+    #[error("Invalid regex: {0}")]
+    InvalidRegex(String),
+
+    #[error(transparent)]
+    InvalidPath(#[from] JsonPathError),
+}
@@ -0,0 +1,154 @@
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::jsonpath::{JsonPath, JsonPathError};
+use crate::models::{ValidationResult, ValidationRule};
+
+/// Name of the per-pack rule file, kept next to modinfo.json
+pub const RULES_FILE: &str = "catalyst_rules.json";
+
+/// Load the rule file from a pack directory.
+/// Returns no rules if the pack doesn't have one.
+pub fn load_rules(pack_path: &Path) -> Result<Vec<ValidationRule>, RuleError> {
+    let path = pack_path.join(RULES_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| RuleError::IoError {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+    let rules: Vec<ValidationRule> =
+        serde_json::from_str(&content).map_err(|e| RuleError::ParseError {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+
+    // Check every rule up front so mistakes show up when the pack loads
+    for rule in &rules {
+        CompiledRule::new(rule, None)?;
+    }
+
+    Ok(rules)
+}
+
+/// A rule with its path and pattern parsed, ready to run against entities
+#[derive(Debug)]
+pub struct CompiledRule<'a> {
+    rule: &'a ValidationRule,
+    path: JsonPath,
+    pattern: Option<Regex>,
+    mod_id: Option<&'a str>,
+}
+
+impl<'a> CompiledRule<'a> {
+    pub fn new(rule: &'a ValidationRule, mod_id: Option<&'a str>) -> Result<Self, RuleError> {
+        if !rule.required && !rule.forbidden && rule.pattern.is_none() && rule.one_of.is_none() {
+            return Err(RuleError::NoCheck(rule.code.clone()));
+        }
+
+        let path = JsonPath::parse(&rule.path).map_err(|source| RuleError::InvalidPath {
+            code: rule.code.clone(),
+            source,
+        })?;
+        let pattern = rule
+            .pattern
+            .as_deref()
+            .map(|pattern| {
+                let pattern = pattern.replace("{mod_id}", &regex::escape(mod_id.unwrap_or("")));
+                Regex::new(&pattern)
+            })
+            .transpose()
+            .map_err(|e| RuleError::InvalidPattern {
+                code: rule.code.clone(),
+                message: e.to_string(),
+            })?;
+
+        Ok(Self {
+            rule,
+            path,
+            pattern,
+            mod_id,
+        })
+    }
+
+    /// Compile a pack's rules, skipping any that are invalid (reported at load)
+    pub fn compile_all(rules: &'a [ValidationRule], mod_id: Option<&'a str>) -> Vec<Self> {
+        rules
+            .iter()
+            .filter_map(|rule| Self::new(rule, mod_id).ok())
+            .collect()
+    }
+
+    /// Run the rule against an entity, adding a warning for each failure
+    pub fn check(&self, entity: &Value, result: &mut ValidationResult) {
+        let entity_type = entity.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        if !self.rule.types.is_empty() && !self.rule.types.iter().any(|t| t == entity_type) {
+            return;
+        }
+
+        let selected = self.path.select(entity);
+
+        if self.rule.required && selected.is_empty() && entity.get("copy-from").is_none() {
+            result.add_warning_with_path(&self.rule.code, self.message(entity, "$", None), "$");
+        }
+
+        for (path, value) in &selected {
+            let failed = self.rule.forbidden
+                || self.pattern.as_ref().is_some_and(|pattern| {
+                    value.as_str().is_none_or(|text| !pattern.is_match(text))
+                })
+                || self
+                    .rule
+                    .one_of
+                    .as_ref()
+                    .is_some_and(|allowed| !allowed.contains(value));
+
+            if failed {
+                result.add_warning_with_path(
+                    &self.rule.code,
+                    self.message(entity, path, Some(value)),
+                    path.as_str(),
+                );
+            }
+        }
+    }
+
+    fn message(&self, entity: &Value, path: &str, value: Option<&Value>) -> String {
+        let field = |name: &str| entity.get(name).and_then(|v| v.as_str()).unwrap_or("");
+        let value = match value {
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+
+        self.rule
+            .message
+            .replace("{id}", field("id"))
+            .replace("{type}", field("type"))
+            .replace("{path}", path)
+            .replace("{value}", &value)
+            .replace("{mod_id}", self.mod_id.unwrap_or(""))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RuleError {
+    #[error("IO error reading {path}: {message}")]
+    IoError { path: PathBuf, message: String },
+
+    #[error("Parse error in {path}: {message}")]
+    ParseError { path: PathBuf, message: String },
+
+    #[error("Rule {code}: {source}")]
+    InvalidPath { code: String, source: JsonPathError },
+
+    #[error("Rule {code}: invalid pattern: {message}")]
+    InvalidPattern { code: String, message: String },
+
+    #[error("Rule {0} has no check (set required, forbidden, pattern or one_of)")]
+    NoCheck(String),
+}
@@ -0,0 +1,334 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use super::jsonpath::child_path;
+use crate::models::{ContentPack, Entity, EntityKey, PackId};
+
+/// Extra weight for words in an entity's ID or name
+const TITLE_WEIGHT: f32 = 3.0;
+
+/// Score multiplier when a field contains the whole query as typed
+const PHRASE_BONUS: f32 = 2.0;
+
+/// Weight of a word that only starts with a query term
+const PREFIX_WEIGHT: f32 = 0.5;
+
+/// Fields shown per result
+const MAX_SNIPPETS: usize = 3;
+
+/// Characters of context kept on each side of the first match in a snippet
+const SNIPPET_CONTEXT: usize = 40;
+
+/// Inverted index over every string value of every loaded entity.
+/// Only words are stored; snippets are cut from the workspace JSON on demand.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// Indexed entities; removed slots are reused
+    documents: Vec<Option<Document>>,
+    free: Vec<usize>,
+    by_key: HashMap<(PackId, EntityKey), usize>,
+    /// Word -> weighted occurrences per document.
+    /// Ordered so a query term can also match words it is a prefix of.
+    postings: BTreeMap<String, HashMap<usize, f32>>,
+}
+
+#[derive(Debug)]
+struct Document {
+    pack_id: PackId,
+    entity_key: EntityKey,
+    /// Distinct words, so the document can be removed from postings
+    words: Vec<String>,
+}
+
+/// A ranked full-text match, before the caller attaches entity details
+#[derive(Debug, Clone)]
+pub struct FullTextHit {
+    pub pack_id: PackId,
+    pub entity_key: EntityKey,
+    pub score: f32,
+}
+
+/// Part of a string value around a match, split so matched words can be highlighted
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    /// JSON path of the string value
+    pub path: String,
+    pub parts: Vec<SnippetPart>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlight: bool,
+}
+
+impl SearchIndex {
+    /// Index every entity in a pack, replacing anything indexed for it before
+    pub fn index_pack(&mut self, pack: &ContentPack) {
+        self.remove_pack(pack.id);
+        for (key, entity) in &pack.entities {
+            self.insert(pack.id, key, entity);
+        }
+    }
+
+    pub fn remove_pack(&mut self, pack_id: PackId) {
+        let keys: Vec<EntityKey> = self
+            .by_key
+            .keys()
+            .filter(|(id, _)| *id == pack_id)
+            .map(|(_, key)| key.clone())
+            .collect();
+        for key in keys {
+            self.remove_entity(pack_id, &key);
+        }
+    }
+
+    /// Re-index an entity after an edit. `old_key` is its key before the
+    /// edit, which differs from `new_key` when its type or ID changed.
+    pub fn update_entity(
+        &mut self,
+        pack_id: PackId,
+        old_key: &str,
+        new_key: &str,
+        entity: &Entity,
+    ) {
+        self.remove_entity(pack_id, old_key);
+        self.insert(pack_id, new_key, entity);
+    }
+
+    fn insert(&mut self, pack_id: PackId, key: &str, entity: &Entity) {
+        self.remove_entity(pack_id, key);
+
+        let mut weights: HashMap<String, f32> = HashMap::new();
+        for (path, text) in string_values(&entity.json) {
+            let weight = if is_title(&path) { TITLE_WEIGHT } else { 1.0 };
+            for (_, word) in words(text) {
+                *weights.entry(word).or_default() += weight;
+            }
+        }
+
+        let doc = self.free.pop().unwrap_or(self.documents.len());
+        for (word, weight) in &weights {
+            self.postings
+                .entry(word.clone())
+                .or_default()
+                .insert(doc, *weight);
+        }
+
+        let document = Document {
+            pack_id,
+            entity_key: key.to_string(),
+            words: weights.into_keys().collect(),
+        };
+        if doc == self.documents.len() {
+            self.documents.push(Some(document));
+        } else {
+            self.documents[doc] = Some(document);
+        }
+        self.by_key.insert((pack_id, key.to_string()), doc);
+    }
+
+    pub fn remove_entity(&mut self, pack_id: PackId, key: &str) {
+        let Some(doc) = self.by_key.remove(&(pack_id, key.to_string())) else {
+            return;
+        };
+        let Some(document) = self.documents[doc].take() else {
+            return;
+        };
+        for word in &document.words {
+            if let Some(docs) = self.postings.get_mut(word) {
+                docs.remove(&doc);
+                if docs.is_empty() {
+                    self.postings.remove(word);
+                }
+            }
+        }
+        self.free.push(doc);
+    }
+
+    /// Entities containing every word of the query, or a longer word it
+    /// starts, best matches first; whole-word matches score higher.
+    /// `entity_json` looks up an entity's current JSON for phrase scoring and
+    /// `accept` filters hits before they are ranked.
+    pub fn search<'w>(
+        &self,
+        query: &str,
+        entity_json: impl Fn(PackId, &str) -> Option<&'w Value>,
+        accept: impl Fn(PackId, &str) -> bool,
+    ) -> Vec<FullTextHit> {
+        let terms: Vec<String> = words(query).map(|(_, word)| word).collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let total = self.by_key.len().max(1) as f32;
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+
+        for (i, term) in terms.iter().enumerate() {
+            let mut term_scores: HashMap<usize, f32> = HashMap::new();
+            for (word, docs) in self.postings.range(term.clone()..) {
+                if !word.starts_with(term.as_str()) {
+                    break;
+                }
+                let weight = if word == term { 1.0 } else { PREFIX_WEIGHT };
+                let idf = (1.0 + total / docs.len() as f32).ln();
+                for (doc, count) in docs {
+                    // Saturate so long entities don't win by repetition alone
+                    let score = weight * idf * count / (count + 1.0);
+                    let best = term_scores.entry(*doc).or_default();
+                    *best = best.max(score);
+                }
+            }
+
+            if i == 0 {
+                scores = term_scores;
+            } else {
+                scores.retain(|doc, score| match term_scores.get(doc) {
+                    Some(term_score) => {
+                        *score += term_score;
+                        true
+                    }
+                    None => false,
+                });
+            }
+            if scores.is_empty() {
+                return Vec::new();
+            }
+        }
+
+        let phrase = query.trim().to_lowercase();
+        let mut hits: Vec<FullTextHit> = scores
+            .into_iter()
+            .filter_map(|(doc, mut score)| {
+                let document = self.documents[doc].as_ref()?;
+                if !accept(document.pack_id, &document.entity_key) {
+                    return None;
+                }
+                if terms.len() > 1 {
+                    let json = entity_json(document.pack_id, &document.entity_key)?;
+                    if string_values(json).any(|(_, text)| text.to_lowercase().contains(&phrase)) {
+                        score *= PHRASE_BONUS;
+                    }
+                }
+                Some(FullTextHit {
+                    pack_id: document.pack_id,
+                    entity_key: document.entity_key.clone(),
+                    score,
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.entity_key.cmp(&b.entity_key))
+                .then_with(|| a.pack_id.cmp(&b.pack_id))
+        });
+        hits
+    }
+}
+
+/// Snippets of the string values in an entity that contain query words,
+/// with those words highlighted
+pub fn snippets(json: &Value, query: &str) -> Vec<Snippet> {
+    let terms: Vec<String> = words(query).map(|(_, word)| word).collect();
+    let matches = |word: &str| terms.iter().any(|term| word.starts_with(term.as_str()));
+
+    let mut snippets = Vec::new();
+    for (path, text) in string_values(json) {
+        let spans: Vec<(usize, usize)> = words(text)
+            .filter(|(_, word)| matches(word))
+            .map(|(start, _)| {
+                let len = text[start..]
+                    .find(|c: char| !c.is_alphanumeric())
+                    .unwrap_or(text.len() - start);
+                (start, start + len)
+            })
+            .collect();
+        let Some(&(first, _)) = spans.first() else {
+            continue;
+        };
+
+        let start = floor_char_boundary(text, first.saturating_sub(SNIPPET_CONTEXT));
+        let end = floor_char_boundary(text, (first + SNIPPET_CONTEXT * 2).min(text.len()));
+        let end = if end < spans[0].1 { spans[0].1 } else { end };
+
+        let mut parts = Vec::new();
+        let mut push = |text: &str, highlight: bool| {
+            if !text.is_empty() {
+                parts.push(SnippetPart {
+                    text: text.to_string(),
+                    highlight,
+                });
+            }
+        };
+
+        if start > 0 {
+            push("…", false);
+        }
+        let mut pos = start;
+        for &(span_start, span_end) in spans.iter().filter(|(s, e)| *s >= start && *e <= end) {
+            push(&text[pos..span_start], false);
+            push(&text[span_start..span_end], true);
+            pos = span_end;
+        }
+        push(&text[pos..end], false);
+        if end < text.len() {
+            push("…", false);
+        }
+
+        snippets.push(Snippet { path, parts });
+        if snippets.len() == MAX_SNIPPETS {
+            break;
+        }
+    }
+
+    snippets
+}
+
+/// Every string value in a JSON tree with its path
+fn string_values(json: &Value) -> impl Iterator<Item = (String, &str)> {
+    let mut stack = vec![("$".to_string(), json)];
+    let mut found = Vec::new();
+    while let Some((path, value)) = stack.pop() {
+        match value {
+            Value::String(s) => found.push((path, s.as_str())),
+            Value::Array(arr) => {
+                for (i, item) in arr.iter().enumerate().rev() {
+                    stack.push((format!("{}[{}]", path, i), item));
+                }
+            }
+            Value::Object(obj) => {
+                for (key, item) in obj.iter().rev() {
+                    stack.push((child_path(&path, key), item));
+                }
+            }
+            _ => {}
+        }
+    }
+    found.into_iter()
+}
+
+/// Lowercased words with their byte offsets
+fn words(text: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(move |word| {
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            (start, word.to_lowercase())
+        })
+}
+
+fn is_title(path: &str) -> bool {
+    matches!(path, "$.id" | "$.name" | "$.name.str")
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
@@ -0,0 +1,360 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::validator::ITEM_TYPES;
+
+/// Kinds of unit strings BN accepts in JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitKind {
+    Volume,
+    Mass,
+    Length,
+    Energy,
+    Money,
+    Duration,
+}
+
+impl UnitKind {
+    /// Accepted unit names and their size in the base unit, largest first.
+    /// Base units are ml, mg, mm, mJ, cents and turns (seconds).
+    pub fn units(self) -> &'static [(&'static str, i64)] {
+        match self {
+            UnitKind::Volume => &[("L", 1000), ("ml", 1)],
+            UnitKind::Mass => &[("kg", 1_000_000), ("g", 1000), ("mg", 1)],
+            UnitKind::Length => &[("km", 1_000_000), ("m", 1000), ("cm", 10), ("mm", 1)],
+            UnitKind::Energy => &[("kJ", 1_000_000), ("J", 1000), ("mJ", 1)],
+            UnitKind::Money => &[("kUSD", 100_000), ("USD", 100), ("cent", 1)],
+            UnitKind::Duration => &[
+                ("days", 86_400),
+                ("day", 86_400),
+                ("d", 86_400),
+                ("hours", 3600),
+                ("hour", 3600),
+                ("h", 3600),
+                ("minutes", 60),
+                ("minute", 60),
+                ("m", 60),
+                ("seconds", 1),
+                ("second", 1),
+                ("s", 1),
+                ("turns", 1),
+                ("turn", 1),
+                ("t", 1),
+            ],
+        }
+    }
+
+    /// Unit names shown in messages (one spelling per size)
+    pub fn unit_names(self) -> Vec<&'static str> {
+        let mut names: Vec<(&str, i64)> = Vec::new();
+        for &(name, size) in self.units() {
+            if !names.iter().any(|(_, s)| *s == size) {
+                names.push((name, size));
+            }
+        }
+        names.into_iter().rev().map(|(name, _)| name).collect()
+    }
+}
+
+/// Kinds tried, in order, when a unit string's field isn't in the unit table
+pub const INFERRED_KINDS: &[UnitKind] = &[
+    UnitKind::Mass,
+    UnitKind::Volume,
+    UnitKind::Length,
+    UnitKind::Energy,
+    UnitKind::Money,
+    UnitKind::Duration,
+];
+
+/// Parse a unit string such as "250 ml", "1 kg" or "3 h 30 m" into base units.
+/// Several number/unit pairs are summed, as the game does.
+pub fn parse(text: &str, kind: UnitKind) -> Result<i64, UnitError> {
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return Err(UnitError::Empty);
+    }
+
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_end]
+            .parse()
+            .map_err(|_| UnitError::InvalidNumber(rest[..number_end].to_string()))?;
+        rest = rest[number_end..].trim_start();
+
+        let unit_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = &rest[..unit_end];
+        let size = kind
+            .units()
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, size)| *size)
+            .ok_or_else(|| UnitError::UnknownUnit {
+                unit: unit.to_string(),
+                expected: kind.unit_names().join(", "),
+            })?;
+        total += number * size as f64;
+        rest = rest[unit_end..].trim_start();
+    }
+
+    Ok(total.round() as i64)
+}
+
+/// Format a base-unit amount as a unit string.
+/// Durations are split into parts ("1 h 30 m"); other kinds use the
+/// largest unit that represents the amount exactly.
+pub fn format(amount: i64, kind: UnitKind) -> String {
+    if kind == UnitKind::Duration {
+        let mut parts = Vec::new();
+        let mut rest = amount;
+        for (name, size) in [("d", 86_400), ("h", 3600), ("m", 60), ("s", 1)] {
+            if rest.abs() >= size {
+                parts.push(format!("{} {}", rest / size, name));
+                rest %= size;
+            }
+        }
+        if parts.is_empty() {
+            return "0 s".to_string();
+        }
+        return parts.join(" ");
+    }
+
+    let (name, size) = kind
+        .units()
+        .iter()
+        .find(|(_, size)| amount % size == 0 && amount != 0)
+        .copied()
+        .unwrap_or_else(|| *kind.units().last().unwrap());
+    format!("{} {}", amount / size, name)
+}
+
+/// A JSON field that holds a unit string
+pub struct UnitField {
+    /// Entity types the field applies to
+    pub types: &'static [&'static str],
+    pub field: &'static str,
+    pub kind: UnitKind,
+    /// How plain integers in this field are interpreted
+    pub legacy: LegacyFormat,
+}
+
+/// Meaning of a plain integer in a unit field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyFormat {
+    /// Integers are not accepted
+    None,
+    /// Each integer step is this many base units
+    Scale(i64),
+    /// Integers are moves (100 per turn)
+    Moves,
+}
+
+/// Fields known to hold unit strings
+pub const UNIT_FIELDS: &[UnitField] = &[
+    UnitField {
+        types: ITEM_TYPES,
+        field: "volume",
+        kind: UnitKind::Volume,
+        legacy: LegacyFormat::Scale(250),
+    },
+    UnitField {
+        types: ITEM_TYPES,
+        field: "integral_volume",
+        kind: UnitKind::Volume,
+        legacy: LegacyFormat::Scale(250),
+    },
+    UnitField {
+        types: ITEM_TYPES,
+        field: "weight",
+        kind: UnitKind::Mass,
+        legacy: LegacyFormat::Scale(1000),
+    },
+    UnitField {
+        types: ITEM_TYPES,
+        field: "integral_weight",
+        kind: UnitKind::Mass,
+        legacy: LegacyFormat::Scale(1000),
+    },
+    UnitField {
+        types: ITEM_TYPES,
+        field: "longest_side",
+        kind: UnitKind::Length,
+        legacy: LegacyFormat::None,
+    },
+    UnitField {
+        types: ITEM_TYPES,
+        field: "integral_longest_side",
+        kind: UnitKind::Length,
+        legacy: LegacyFormat::None,
+    },
+    UnitField {
+        types: ITEM_TYPES,
+        field: "price",
+        kind: UnitKind::Money,
+        legacy: LegacyFormat::Scale(1),
+    },
+    UnitField {
+        types: ITEM_TYPES,
+        field: "price_postapoc",
+        kind: UnitKind::Money,
+        legacy: LegacyFormat::Scale(1),
+    },
+    UnitField {
+        types: &["COMESTIBLE"],
+        field: "spoils_in",
+        kind: UnitKind::Duration,
+        legacy: LegacyFormat::Scale(3600),
+    },
+    UnitField {
+        types: &["MONSTER"],
+        field: "volume",
+        kind: UnitKind::Volume,
+        legacy: LegacyFormat::None,
+    },
+    UnitField {
+        types: &["MONSTER"],
+        field: "weight",
+        kind: UnitKind::Mass,
+        legacy: LegacyFormat::None,
+    },
+    UnitField {
+        types: &["recipe", "uncraft"],
+        field: "time",
+        kind: UnitKind::Duration,
+        legacy: LegacyFormat::Moves,
+    },
+    UnitField {
+        types: &["bionic"],
+        field: "capacity",
+        kind: UnitKind::Energy,
+        legacy: LegacyFormat::Scale(1_000_000),
+    },
+    UnitField {
+        types: &["bionic"],
+        field: "act_cost",
+        kind: UnitKind::Energy,
+        legacy: LegacyFormat::Scale(1_000_000),
+    },
+    UnitField {
+        types: &["bionic"],
+        field: "react_cost",
+        kind: UnitKind::Energy,
+        legacy: LegacyFormat::Scale(1_000_000),
+    },
+    UnitField {
+        types: &["bionic"],
+        field: "trigger_cost",
+        kind: UnitKind::Energy,
+        legacy: LegacyFormat::Scale(1_000_000),
+    },
+];
+
+/// Unit fields that apply to an entity type
+pub fn fields_for_type(entity_type: &str) -> impl Iterator<Item = &'static UnitField> + '_ {
+    UNIT_FIELDS
+        .iter()
+        .filter(move |f| f.types.contains(&entity_type))
+}
+
+/// Convert legacy integer unit fields of an entity to unit strings.
+/// Returns the number of values converted.
+pub fn normalize_legacy_values(json: &mut Value) -> usize {
+    let entity_type = json
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let Some(obj) = json.as_object_mut() else {
+        return 0;
+    };
+
+    let mut converted = 0;
+    for field in fields_for_type(&entity_type) {
+        let Some(amount) = obj.get(field.field).and_then(|v| v.as_i64()) else {
+            continue;
+        };
+        let Some(text) = field.legacy_to_string(amount) else {
+            continue;
+        };
+        obj.insert(field.field.to_string(), Value::String(text));
+        converted += 1;
+    }
+
+    converted
+}
+
+impl UnitField {
+    /// The unit string equivalent to a legacy integer value, if it has one.
+    /// Negative values and values too large to convert are left alone.
+    pub fn legacy_to_string(&self, amount: i64) -> Option<String> {
+        if amount < 0 {
+            return None;
+        }
+        let base = match self.legacy {
+            LegacyFormat::None => return None,
+            LegacyFormat::Scale(scale) => amount.checked_mul(scale)?,
+            // Only whole turns can be written as a duration
+            LegacyFormat::Moves if amount % 100 == 0 => amount / 100,
+            LegacyFormat::Moves => return None,
+        };
+        Some(format(base, self.kind))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UnitError {
+    #[error("empty value")]
+    Empty,
+
+    #[error("'{0}' is not a number")]
+    InvalidNumber(String),
+
+    #[error("unknown unit '{unit}' (expected {expected})")]
+    UnknownUnit { unit: String, expected: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_sums_units() {
+        assert_eq!(parse("250 ml", UnitKind::Volume).unwrap(), 250);
+        assert_eq!(parse("1.5 L", UnitKind::Volume).unwrap(), 1500);
+        assert_eq!(parse("3 h 30 m", UnitKind::Duration).unwrap(), 12_600);
+        assert!(matches!(
+            parse("2 lb", UnitKind::Mass),
+            Err(UnitError::UnknownUnit { .. })
+        ));
+        assert!(matches!(parse(" ", UnitKind::Mass), Err(UnitError::Empty)));
+    }
+
+    #[test]
+    fn formats_with_the_largest_exact_unit() {
+        assert_eq!(format(1500, UnitKind::Volume), "1500 ml");
+        assert_eq!(format(2000, UnitKind::Volume), "2 L");
+        assert_eq!(format(5400, UnitKind::Duration), "1 h 30 m");
+        assert_eq!(format(0, UnitKind::Duration), "0 s");
+    }
+
+    #[test]
+    fn converts_legacy_integers() {
+        let mut item = serde_json::json!({
+            "type": "GENERIC",
+            "id": "rock",
+            "volume": 2,
+            "weight": i64::MAX,
+            "price": -100
+        });
+        assert_eq!(normalize_legacy_values(&mut item), 1);
+        assert_eq!(item["volume"], "500 ml");
+        // Too large to scale, or negative, so left as it is
+        assert_eq!(item["weight"], i64::MAX);
+        assert_eq!(item["price"], -100);
+    }
+}
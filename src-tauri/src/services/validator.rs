@@ -1,11 +1,295 @@
 use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use crate::models::{EntityMeta, ValidationResult};
+use crate::models::{
+    ContentPack, EntityMeta, Fix, Mapgen, PatchOperation, ValidationResult, Workspace,
+    SYMBOL_SECTIONS,
+};
+use crate::services::fixes;
+use crate::services::flags::{FlagCategory, FlagRegistry};
+use crate::services::fuzzy;
+use crate::services::jsonpath::child_path;
+use crate::services::migrations;
+use crate::services::palette;
+use crate::services::rules::CompiledRule;
+use crate::services::units::{self, LegacyFormat, UnitKind};
+
+/// Entity types that define items
+pub const ITEM_TYPES: &[&str] = &[
+    "GENERIC",
+    "AMMO",
+    "ARMOR",
+    "BATTERY",
+    "BIONIC_ITEM",
+    "BOOK",
+    "COMESTIBLE",
+    "CONTAINER",
+    "ENGINE",
+    "GUN",
+    "GUNMOD",
+    "MAGAZINE",
+    "PET_ARMOR",
+    "TOOL",
+    "TOOLMOD",
+    "TOOL_ARMOR",
+    "WHEEL",
+];
+
+/// Highest skill level the game allows
+const MAX_SKILL: i64 = 10;
+
+/// Damage types accepted in damage instances
+const DAMAGE_TYPES: &[&str] = &[
+    "pure",
+    "biological",
+    "bash",
+    "cut",
+    "acid",
+    "stab",
+    "bullet",
+    "heat",
+    "cold",
+    "electric",
+];
+
+/// Death functions implemented by the game engine
+const DEATH_FUNCTIONS: &[&str] = &[
+    "ACID",
+    "AMIGARA",
+    "BLOBSPLIT",
+    "BOOMER",
+    "BOOMER_GLOW",
+    "BRAINBLOB",
+    "BROKEN",
+    "BROKEN_AMMO",
+    "CONFLAGRATION",
+    "DARKMAN",
+    "DETONATE",
+    "DISAPPEAR",
+    "DISINTEGRATE",
+    "EXPLODE",
+    "FIREBALL",
+    "FOCUSEDBEAM",
+    "FUNGUS",
+    "GAMEOVER",
+    "GAS",
+    "GUILT",
+    "JABBERWOCKY",
+    "JACKSON",
+    "KILL_BREATHERS",
+    "KILL_VINES",
+    "MELT",
+    "NORMAL",
+    "PREG_ROACH",
+    "RATKING",
+    "SMOKEBURST",
+    "SPLATTER",
+    "THING",
+    "TRIFFID_HEART",
+    "VINE_CUT",
+    "WORM",
+];
+
+/// Special attacks implemented in the game engine rather than as `monster_attack` JSON
+const BUILTIN_ATTACKS: &[&str] = &[
+    "ABSORB_MEAT",
+    "ACID",
+    "ACID_ACCURATE",
+    "ACID_BARF",
+    "BIO_OP_BIOJUTSU",
+    "BIO_OP_DISARM",
+    "BIO_OP_IMPALE",
+    "BIO_OP_TAKEDOWN",
+    "BITE",
+    "BOOMER",
+    "BOOMER_GLOW",
+    "BRANDISH",
+    "BREATHE",
+    "CALLBLOBS",
+    "CHECK_UP",
+    "CHICKENBOT",
+    "COPBOT",
+    "DANCE",
+    "DARKMAN",
+    "DERMATIK",
+    "DERMATIK_GROWTH",
+    "DISAPPEAR",
+    "DOGTHING",
+    "EAT_CROP",
+    "EAT_FOOD",
+    "FEAR_PARALYZE",
+    "FLAMETHROWER",
+    "FLESH_GOLEM",
+    "FORMBLOB",
+    "FUNGAL_TRAIL",
+    "FUNGUS",
+    "FUNGUS_BIG_BLOSSOM",
+    "FUNGUS_BRISTLE",
+    "FUNGUS_CORPORATE",
+    "FUNGUS_FORTIFY",
+    "FUNGUS_GROWTH",
+    "FUNGUS_HAZE",
+    "FUNGUS_INJECT",
+    "FUNGUS_SPROUT",
+    "GENERATOR",
+    "GRAB",
+    "GRAB_DRAG",
+    "GROWPLANTS",
+    "GROW_VINE",
+    "HOWL",
+    "IMPALE",
+    "JACKSON",
+    "KAMIKAZE",
+    "LEAP",
+    "LONGSWIPE",
+    "LUNGE",
+    "MULTI_ROBOT",
+    "NONE",
+    "NURSE_ASSIST",
+    "NURSE_CHECK_UP",
+    "NURSE_OPERATE",
+    "PARA_STING",
+    "PARROT",
+    "PARROT_AT_DANGER",
+    "PHOTOGRAPH",
+    "PLANT",
+    "PULL_METAL_WEAPON",
+    "RANGED_PULL",
+    "RATTLE",
+    "RATKING",
+    "RESURRECT",
+    "RIOTBOT",
+    "SCIENCE",
+    "SEARCHLIGHT",
+    "SHOCKSTORM",
+    "SHRIEK",
+    "SHRIEK_ALERT",
+    "SHRIEK_STUN",
+    "SLIMESPRING",
+    "SMASH",
+    "SMG",
+    "SPIT_SAP",
+    "STARE",
+    "STRETCH_ATTACK",
+    "STRETCH_BITE",
+    "SUICIDE",
+    "TAZER",
+    "TENTACLE",
+    "TINDALOS_TELEPORT",
+    "TRIFFID_GROWTH",
+    "TRIFFID_HEARTBEAT",
+    "UPGRADE",
+    "VINE",
+    "VORTEX",
+    "ZOMBIE_FUSE",
+];
+
+/// Entities already loaded in the workspace, used to check references.
+///
+/// Reference checks are only performed for entity types the context knows
+/// about, so validating without the base game loaded doesn't flag every ID.
+#[derive(Default)]
+pub struct ValidationContext<'a> {
+    /// Entity JSON by type, then by ID (later packs in load order win)
+    entities: HashMap<&'a str, HashMap<&'a str, &'a Value>>,
+    /// Flags declared by json_flag entities plus engine flags
+    flags: FlagRegistry,
+    /// Custom rules of the pack being edited
+    rules: Vec<CompiledRule<'a>>,
+}
+
+impl<'a> ValidationContext<'a> {
+    /// Build a context from every pack in the workspace, respecting load order
+    pub fn from_workspace(workspace: &'a Workspace) -> Self {
+        let mut entities: HashMap<&'a str, HashMap<&'a str, &'a Value>> = HashMap::new();
+
+        for pack in workspace
+            .load_order
+            .iter()
+            .filter_map(|id| workspace.packs.get(id))
+        {
+            for entity in pack.entities.values() {
+                entities
+                    .entry(entity.meta.entity_type.as_str())
+                    .or_default()
+                    .insert(entity.meta.id.as_str(), &entity.json);
+            }
+        }
+
+        let flags = FlagRegistry::new(
+            entities
+                .get("json_flag")
+                .into_iter()
+                .flat_map(|ids| ids.values().copied()),
+        );
+
+        Self {
+            entities,
+            flags,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Also run the custom rules of the pack the entity belongs to
+    pub fn with_pack(mut self, pack: &'a ContentPack) -> Self {
+        let mod_id = pack.metadata.as_ref().and_then(|m| m.mod_id.as_deref());
+        self.rules = CompiledRule::compile_all(&pack.rules, mod_id);
+        self
+    }
+
+    /// Known flags for checking `flags` arrays
+    pub fn flags(&self) -> &FlagRegistry {
+        &self.flags
+    }
+
+    /// Whether any entity of the given types is loaded
+    pub fn knows_any(&self, types: &[&str]) -> bool {
+        types
+            .iter()
+            .any(|t| self.entities.get(t).is_some_and(|ids| !ids.is_empty()))
+    }
+
+    /// Look up an entity by ID among the given types
+    pub fn get(&self, types: &[&str], id: &str) -> Option<&'a Value> {
+        types
+            .iter()
+            .find_map(|t| self.entities.get(t).and_then(|ids| ids.get(id)).copied())
+    }
+
+    /// Whether an entity with this ID exists among the given types
+    pub fn contains(&self, types: &[&str], id: &str) -> bool {
+        self.get(types, id).is_some()
+    }
+
+    /// Look up a field of an entity, following `copy-from` if it isn't set directly
+    pub fn field(&self, types: &[&str], id: &str, field: &str) -> Option<&'a Value> {
+        let mut current = self.get(types, id)?;
+        // Bound the walk so a copy-from cycle can't loop forever
+        for _ in 0..16 {
+            if let Some(value) = current.get(field) {
+                return Some(value);
+            }
+            let parent = current.get("copy-from").and_then(|v| v.as_str())?;
+            current = self.get(types, parent)?;
+        }
+        None
+    }
+}
 
 /// Validate JSON text and return a validation result
+#[allow(dead_code)]
 pub fn validate_json_text(text: &str) -> ValidationResult {
+    validate_json_text_with_context(text, &ValidationContext::default())
+}
+
+/// Validate JSON text, checking references against loaded entities
+pub fn validate_json_text_with_context(text: &str, ctx: &ValidationContext) -> ValidationResult {
     match serde_json::from_str::<Value>(text) {
-        Ok(value) => validate_entity_json(&value),
+        Ok(value) => {
+            let mut result = validate_entity_json_with_context(&value, ctx);
+            annotate_lines(&mut result, text);
+            result
+        }
         Err(e) => {
             let mut result = ValidationResult::default();
             result.add_error(
@@ -25,6 +309,14 @@ pub fn validate_json_text(text: &str) -> ValidationResult {
 
 /// Validate a parsed JSON value as an entity
 pub fn validate_entity_json(value: &Value) -> ValidationResult {
+    validate_entity_json_with_context(value, &ValidationContext::default())
+}
+
+/// Validate a parsed JSON value as an entity, checking references against loaded entities
+pub fn validate_entity_json_with_context(
+    value: &Value,
+    ctx: &ValidationContext,
+) -> ValidationResult {
     let mut result = ValidationResult::ok();
 
     // Must be an object
@@ -46,8 +338,13 @@ pub fn validate_entity_json(value: &Value) -> ValidationResult {
     // Check for ID field (with exceptions)
     let is_abstract = value.get("abstract").is_some();
     let is_recipe = entity_type == "recipe" || entity_type == "uncraft";
+    // Mapgen is identified by the terrain it generates or its nested/update ID
+    let is_mapgen = entity_type == "mapgen"
+        && ["om_terrain", "nested_mapgen_id", "update_mapgen_id"]
+            .iter()
+            .any(|field| value.get(field).is_some());
 
-    if !is_abstract {
+    if !is_abstract && !is_mapgen {
         let has_id = value.get("id").is_some();
         let has_result = value.get("result").is_some();
 
@@ -61,27 +358,58 @@ pub fn validate_entity_json(value: &Value) -> ValidationResult {
     }
 
     // Type-specific validation
-    result.merge(validate_type_specific(value, entity_type));
+    result.merge(validate_type_specific(value, entity_type, ctx));
+
+    // Team-defined rules run after the built-in checks
+    for rule in &ctx.rules {
+        rule.check(value, &mut result);
+    }
 
     result
 }
 
 /// Type-specific validation rules
-fn validate_type_specific(value: &Value, entity_type: &str) -> ValidationResult {
+fn validate_type_specific(
+    value: &Value,
+    entity_type: &str,
+    ctx: &ValidationContext,
+) -> ValidationResult {
     let mut result = ValidationResult::ok();
 
+    validate_unit_fields(value, entity_type, &mut result);
+
+    for deprecation in migrations::find_deprecated(value) {
+        match deprecation.fix {
+            Some(fix) => result.add_warning_with_fix(
+                "DEPRECATED_FIELD",
+                deprecation.message,
+                deprecation.path,
+                fix,
+            ),
+            None => result.add_warning_with_path(
+                "DEPRECATED_FIELD",
+                deprecation.message,
+                deprecation.path,
+            ),
+        }
+    }
+
+    if let Some(category) = FlagCategory::for_type(entity_type) {
+        validate_flags(value, category, ctx, &mut result);
+    }
+
     match entity_type {
         "recipe" | "uncraft" => {
-            validate_recipe(value, &mut result);
+            validate_recipe(value, ctx, &mut result);
         }
         "MONSTER" => {
-            validate_monster(value, &mut result);
+            validate_monster(value, ctx, &mut result);
         }
         "vehicle" => {
-            validate_vehicle(value, &mut result);
+            validate_vehicle(value, ctx, &mut result);
         }
         "mapgen" => {
-            validate_mapgen(value, &mut result);
+            validate_mapgen(value, ctx, &mut result);
         }
         _ => {
             // Generic item types and others - no specific validation yet
@@ -91,12 +419,115 @@ fn validate_type_specific(value: &Value, entity_type: &str) -> ValidationResult
     result
 }
 
-fn validate_recipe(value: &Value, result: &mut ValidationResult) {
+/// Check `flags` against the flag registry, suggesting the closest known flag.
+/// Monster flags are all hard-coded, so they're always checked; other
+/// categories rely on json_flag entities and are only checked once the
+/// base game's flags are loaded.
+fn validate_flags(
+    value: &Value,
+    category: FlagCategory,
+    ctx: &ValidationContext,
+    result: &mut ValidationResult,
+) {
+    let Some(flags) = value.get("flags") else {
+        return;
+    };
+    let registry = ctx.flags();
+    let check_unknown = category == FlagCategory::Monster || registry.has_json_flags();
+
+    for_each_string(
+        flags,
+        "$.flags",
+        "INVALID_FLAGS",
+        result,
+        |flag, path, result| {
+            if !check_unknown || registry.is_known(category, flag) {
+                return;
+            }
+            let suggestion = fuzzy::closest_match(flag, registry.candidates(category));
+            match (suggestion, fixes::pointer_from_path(&path)) {
+                (Some(suggestion), Some(pointer)) => result.add_warning_with_fix(
+                    "UNKNOWN_FLAG",
+                    format!("Unknown flag '{}' (did you mean '{}'?)", flag, suggestion),
+                    path,
+                    Fix::new(
+                        format!("Replace '{}' with '{}'", flag, suggestion),
+                        vec![PatchOperation::Replace {
+                            path: pointer,
+                            value: Value::String(suggestion.to_string()),
+                        }],
+                    ),
+                ),
+                _ => result.add_warning_with_path(
+                    "UNKNOWN_FLAG",
+                    format!("Unknown flag '{}'", flag),
+                    path,
+                ),
+            }
+        },
+    );
+}
+
+/// Check fields that hold unit strings ("250 ml", "1 kg", "3 h 30 m", ...)
+fn validate_unit_fields(value: &Value, entity_type: &str, result: &mut ValidationResult) {
+    for field in units::fields_for_type(entity_type) {
+        let Some(amount) = value.get(field.field) else {
+            continue;
+        };
+        let path = format!("$.{}", field.field);
+        let code = if field.kind == UnitKind::Duration {
+            "INVALID_DURATION"
+        } else {
+            "INVALID_UNIT"
+        };
+
+        match amount {
+            Value::String(text) => {
+                if let Err(e) = units::parse(text, field.kind) {
+                    result.add_error_with_path(
+                        code,
+                        format!("'{}' is not a valid unit value: {}", field.field, e),
+                        path,
+                    );
+                }
+            }
+            Value::Number(n) if field.legacy != LegacyFormat::None && n.is_i64() => {
+                if n.as_i64().is_some_and(|n| n < 0) {
+                    result.add_error_with_path(
+                        code,
+                        format!("'{}' can't be negative, found {}", field.field, n),
+                        path,
+                    );
+                }
+            }
+            _ => result.add_error_with_path(
+                code,
+                format!(
+                    "'{}' must be a string with a unit ({}), found {}",
+                    field.field,
+                    field.kind.unit_names().join(", "),
+                    amount
+                ),
+                path,
+            ),
+        }
+    }
+}
+
+fn validate_recipe(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
     // Recipes should have category and subcategory
     if value.get("category").is_none() && value.get("copy-from").is_none() {
-        result.add_warning(
+        result.add_warning_with_fix(
             "MISSING_CATEGORY",
             "Recipe should have a 'category' field for menu organization",
+            "$",
+            Fix::new(
+                "Add category CC_OTHER",
+                vec![PatchOperation::Add {
+                    path: "/category".to_string(),
+                    value: Value::String("CC_OTHER".to_string()),
+                }],
+            ),
         );
     }
 
@@ -110,9 +541,368 @@ fn validate_recipe(value: &Value, result: &mut ValidationResult) {
             "Recipe has no 'components' or 'using' field",
         );
     }
+
+    if let Some(id) = value.get("result").and_then(|v| v.as_str()) {
+        check_reference(ctx, result, ITEM_TYPES, "item", id, "$.result");
+    }
+
+    if let Some(components) = value.get("components") {
+        validate_requirement_list(components, "components", ctx, result);
+    }
+    if let Some(tools) = value.get("tools") {
+        validate_requirement_list(tools, "tools", ctx, result);
+    }
+    if let Some(using) = value.get("using") {
+        validate_using(using, ctx, result);
+    }
+    if let Some(qualities) = value.get("qualities") {
+        validate_qualities(qualities, ctx, result);
+    }
+    if let Some(byproducts) = value.get("byproducts") {
+        validate_byproducts(byproducts, ctx, result);
+    }
+
+    if let Some(skill) = value.get("skill_used") {
+        match skill.as_str() {
+            Some(id) => check_reference(ctx, result, &["skill"], "skill", id, "$.skill_used"),
+            None => result.add_error_with_path(
+                "INVALID_SKILL",
+                "'skill_used' must be a skill ID string",
+                "$.skill_used",
+            ),
+        }
+    }
+    if let Some(skills) = value.get("skills_required") {
+        validate_skills_required(skills, ctx, result);
+    }
+
+    if let Some(difficulty) = value.get("difficulty") {
+        match difficulty.as_i64() {
+            Some(level) if (0..=MAX_SKILL).contains(&level) => {}
+            Some(_) => result.add_warning_with_path(
+                "INVALID_DIFFICULTY",
+                format!("'difficulty' should be from 0 to {}", MAX_SKILL),
+                "$.difficulty",
+            ),
+            None => result.add_error_with_path(
+                "INVALID_DIFFICULTY",
+                "'difficulty' must be an integer",
+                "$.difficulty",
+            ),
+        }
+    }
+}
+
+/// Validate `components` or `tools`: a list of groups, each a list of
+/// alternatives of the form `[id, count]` or `[requirement_id, count, "LIST"]`
+fn validate_requirement_list(
+    value: &Value,
+    field: &str,
+    ctx: &ValidationContext,
+    result: &mut ValidationResult,
+) {
+    let Some(groups) = value.as_array() else {
+        result.add_error_with_path(
+            "INVALID_REQUIREMENTS",
+            format!("'{}' must be an array of alternative groups", field),
+            format!("$.{}", field),
+        );
+        return;
+    };
+
+    for (i, group) in groups.iter().enumerate() {
+        let group_path = format!("$.{}[{}]", field, i);
+        let Some(alternatives) = group.as_array().filter(|a| !a.is_empty()) else {
+            result.add_error_with_path(
+                "INVALID_REQUIREMENTS",
+                format!(
+                    "Each entry of '{}' must be a non-empty array of alternatives",
+                    field
+                ),
+                group_path,
+            );
+            continue;
+        };
+
+        for (j, alternative) in alternatives.iter().enumerate() {
+            let path = format!("{}[{}]", group_path, j);
+            let parts = alternative
+                .as_array()
+                .map(|a| a.as_slice())
+                .unwrap_or_default();
+
+            let (Some(id), Some(count)) = (
+                parts.first().and_then(|v| v.as_str()),
+                parts.get(1).and_then(|v| v.as_i64()),
+            ) else {
+                result.add_error_with_path(
+                    "INVALID_REQUIREMENTS",
+                    format!("Expected [id, count] in '{}', found {}", field, alternative),
+                    path,
+                );
+                continue;
+            };
+
+            let marker = parts.get(2).and_then(|v| v.as_str());
+            if parts.len() > 3
+                || (parts.len() == 3 && !matches!(marker, Some("LIST" | "NO_RECOVER")))
+            {
+                result.add_warning_with_path(
+                    "INVALID_REQUIREMENTS",
+                    format!(
+                        "Unexpected extra values in '{}' entry {}",
+                        field, alternative
+                    ),
+                    path.clone(),
+                );
+            }
+
+            // Tools use -1 for "no charges consumed"
+            let count_ok = if field == "tools" {
+                count == -1 || count > 0
+            } else {
+                count > 0
+            };
+            if !count_ok {
+                result.add_warning_with_path(
+                    "INVALID_COUNT",
+                    format!("Invalid count {} for '{}'", count, id),
+                    format!("{}[1]", path),
+                );
+            }
+
+            let id_path = format!("{}[0]", path);
+            if marker == Some("LIST") {
+                check_reference(ctx, result, &["requirement"], "requirement", id, id_path);
+            } else {
+                check_reference(ctx, result, ITEM_TYPES, "item", id, id_path);
+            }
+        }
+    }
+}
+
+/// Validate `using`: a requirement ID or a list of `[requirement_id, multiplier]`
+fn validate_using(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
+    if let Some(id) = value.as_str() {
+        check_reference(ctx, result, &["requirement"], "requirement", id, "$.using");
+        return;
+    }
+
+    let Some(entries) = value.as_array() else {
+        result.add_error_with_path(
+            "INVALID_USING",
+            "'using' must be a requirement ID or an array of [id, multiplier]",
+            "$.using",
+        );
+        return;
+    };
+
+    for (i, entry) in entries.iter().enumerate() {
+        let path = format!("$.using[{}]", i);
+        let parts = entry.as_array().map(|a| a.as_slice()).unwrap_or_default();
+        match (
+            parts.first().and_then(|v| v.as_str()),
+            parts.get(1).and_then(|v| v.as_i64()),
+        ) {
+            (Some(id), Some(multiplier)) if parts.len() == 2 => {
+                check_reference(
+                    ctx,
+                    result,
+                    &["requirement"],
+                    "requirement",
+                    id,
+                    format!("{}[0]", path),
+                );
+                if multiplier <= 0 {
+                    result.add_warning_with_path(
+                        "INVALID_USING",
+                        format!("Multiplier for '{}' should be positive", id),
+                        format!("{}[1]", path),
+                    );
+                }
+            }
+            _ => result.add_error_with_path(
+                "INVALID_USING",
+                format!(
+                    "Expected [requirement_id, multiplier] in 'using', found {}",
+                    entry
+                ),
+                path,
+            ),
+        }
+    }
 }
 
-fn validate_monster(value: &Value, result: &mut ValidationResult) {
+/// Validate `qualities`: a list of `{ "id", "level" }` objects, or lists of them as alternatives
+fn validate_qualities(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
+    let Some(entries) = value.as_array() else {
+        result.add_error_with_path(
+            "INVALID_QUALITIES",
+            "'qualities' must be an array",
+            "$.qualities",
+        );
+        return;
+    };
+
+    for (i, entry) in entries.iter().enumerate() {
+        let path = format!("$.qualities[{}]", i);
+        match entry {
+            Value::Array(alternatives) => {
+                for (j, alternative) in alternatives.iter().enumerate() {
+                    validate_quality(alternative, format!("{}[{}]", path, j), ctx, result);
+                }
+            }
+            _ => validate_quality(entry, path, ctx, result),
+        }
+    }
+}
+
+fn validate_quality(
+    value: &Value,
+    path: String,
+    ctx: &ValidationContext,
+    result: &mut ValidationResult,
+) {
+    let Some(id) = value.get("id").and_then(|v| v.as_str()) else {
+        result.add_error_with_path(
+            "INVALID_QUALITIES",
+            format!("Quality requirement must have an 'id', found {}", value),
+            path,
+        );
+        return;
+    };
+
+    check_reference(
+        ctx,
+        result,
+        &["tool_quality"],
+        "tool quality",
+        id,
+        format!("{}.id", path),
+    );
+
+    match value.get("level").map(|v| v.as_i64()) {
+        None => {}
+        Some(Some(level)) if level >= 1 => {}
+        Some(Some(_)) => result.add_warning_with_path(
+            "INVALID_QUALITY_LEVEL",
+            format!("Quality '{}' should have a positive 'level'", id),
+            format!("{}.level", path),
+        ),
+        Some(None) => result.add_error_with_path(
+            "INVALID_QUALITY_LEVEL",
+            format!("Quality '{}' must have an integer 'level'", id),
+            format!("{}.level", path),
+        ),
+    }
+}
+
+/// Validate `byproducts`: a list of `[id]` or `[id, count]`
+fn validate_byproducts(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
+    let Some(entries) = value.as_array() else {
+        result.add_error_with_path(
+            "INVALID_BYPRODUCTS",
+            "'byproducts' must be an array of [id, count]",
+            "$.byproducts",
+        );
+        return;
+    };
+
+    for (i, entry) in entries.iter().enumerate() {
+        let path = format!("$.byproducts[{}]", i);
+        let parts = entry.as_array().map(|a| a.as_slice()).unwrap_or_default();
+        let id = parts.first().and_then(|v| v.as_str());
+        let count = parts.get(1).map(|count| count.as_i64());
+
+        match (id, count) {
+            (Some(id), None | Some(Some(_))) if parts.len() <= 2 => {
+                check_reference(ctx, result, ITEM_TYPES, "item", id, format!("{}[0]", path));
+                if let Some(Some(count @ ..=0)) = count {
+                    result.add_warning_with_path(
+                        "INVALID_BYPRODUCTS",
+                        format!("Count for '{}' should be positive, found {}", id, count),
+                        format!("{}[1]", path),
+                    );
+                }
+            }
+            _ => result.add_error_with_path(
+                "INVALID_BYPRODUCTS",
+                format!("Expected [id, count] in 'byproducts', found {}", entry),
+                path,
+            ),
+        }
+    }
+}
+
+/// Validate `skills_required`: `[skill, level]` or a list of them
+fn validate_skills_required(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
+    let entries: Vec<(String, &Value)> = match value.as_array() {
+        // A single pair: ["survival", 2]
+        Some(arr) if arr.first().is_some_and(|v| v.is_string()) => {
+            vec![("$.skills_required".to_string(), value)]
+        }
+        Some(arr) => arr
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("$.skills_required[{}]", i), v))
+            .collect(),
+        None => {
+            result.add_error_with_path(
+                "INVALID_SKILL",
+                "'skills_required' must be [skill, level] or an array of them",
+                "$.skills_required",
+            );
+            return;
+        }
+    };
+
+    for (path, entry) in entries {
+        let parts = entry.as_array().map(|a| a.as_slice()).unwrap_or_default();
+        match (
+            parts.first().and_then(|v| v.as_str()),
+            parts.get(1).and_then(|v| v.as_i64()),
+        ) {
+            (Some(id), Some(level)) if parts.len() == 2 => {
+                check_reference(ctx, result, &["skill"], "skill", id, format!("{}[0]", path));
+                if !(0..=MAX_SKILL).contains(&level) {
+                    result.add_warning_with_path(
+                        "INVALID_SKILL",
+                        format!("Skill level for '{}' should be from 0 to {}", id, MAX_SKILL),
+                        format!("{}[1]", path),
+                    );
+                }
+            }
+            _ => result.add_error_with_path(
+                "INVALID_SKILL",
+                format!(
+                    "Expected [skill, level] in 'skills_required', found {}",
+                    entry
+                ),
+                path,
+            ),
+        }
+    }
+}
+
+/// Warn if `id` doesn't match any loaded entity of the given types
+fn check_reference(
+    ctx: &ValidationContext,
+    result: &mut ValidationResult,
+    types: &[&str],
+    kind: &str,
+    id: &str,
+    path: impl Into<String>,
+) {
+    if ctx.knows_any(types) && !ctx.contains(types, id) {
+        result.add_warning_with_path(
+            "UNKNOWN_REFERENCE",
+            format!("Unknown {} '{}'", kind, id),
+            path,
+        );
+    }
+}
+
+fn validate_monster(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
     // Monsters should have basic stats unless copying
     if value.get("copy-from").is_none() {
         if value.get("hp").is_none() {
@@ -122,33 +912,930 @@ fn validate_monster(value: &Value, result: &mut ValidationResult) {
             result.add_warning("MISSING_SPEED", "Monster should have a 'speed' field");
         }
     }
+
+    if let Some(species) = value.get("species") {
+        for_each_string(
+            species,
+            "$.species",
+            "INVALID_SPECIES",
+            result,
+            |id, path, result| {
+                check_reference(ctx, result, &["SPECIES"], "species", id, path);
+            },
+        );
+    }
+
+    if let Some(material) = value.get("material") {
+        for_each_string(
+            material,
+            "$.material",
+            "INVALID_MATERIAL",
+            result,
+            |id, path, result| {
+                check_reference(ctx, result, &["material"], "material", id, path);
+            },
+        );
+    }
+
+    if let Some(attacks) = value.get("special_attacks") {
+        validate_special_attacks(attacks, ctx, result);
+    }
+
+    if let Some(death_function) = value.get("death_function") {
+        for_each_string(
+            death_function,
+            "$.death_function",
+            "INVALID_DEATH_FUNCTION",
+            result,
+            |name, path, result| {
+                if !DEATH_FUNCTIONS.contains(&name) {
+                    result.add_warning_with_path(
+                        "UNKNOWN_DEATH_FUNCTION",
+                        format!("Unknown death function '{}'", name),
+                        path,
+                    );
+                }
+            },
+        );
+    }
+
+    if let Some(harvest) = value.get("harvest") {
+        match harvest.as_str() {
+            Some(id) => check_reference(ctx, result, &["harvest"], "harvest", id, "$.harvest"),
+            None => result.add_error_with_path(
+                "INVALID_HARVEST",
+                "'harvest' must be a harvest ID string",
+                "$.harvest",
+            ),
+        }
+    }
+
+    // death_drops is either an item group ID or an inline item group
+    if let Some(id) = value.get("death_drops").and_then(|v| v.as_str()) {
+        check_reference(
+            ctx,
+            result,
+            &["item_group"],
+            "item group",
+            id,
+            "$.death_drops",
+        );
+    }
+
+    if let Some(upgrades) = value.get("upgrades") {
+        validate_upgrades(upgrades, ctx, result);
+    }
+
+    if let Some(melee_damage) = value.get("melee_damage") {
+        validate_damage_instance(melee_damage, "$.melee_damage", result);
+    }
+}
+
+/// Validate `special_attacks`: `[id, cooldown]` pairs, references to
+/// `monster_attack` entities, or inline attack objects
+fn validate_special_attacks(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
+    let Some(attacks) = value.as_array() else {
+        result.add_error_with_path(
+            "INVALID_SPECIAL_ATTACK",
+            "'special_attacks' must be an array",
+            "$.special_attacks",
+        );
+        return;
+    };
+
+    for (i, attack) in attacks.iter().enumerate() {
+        let path = format!("$.special_attacks[{}]", i);
+        match attack {
+            Value::Array(parts) => {
+                let id = parts.first().and_then(|v| v.as_str());
+                let cooldown_ok = parts
+                    .get(1)
+                    .is_none_or(|v| v.as_i64().is_some_and(|n| n >= 0));
+                match id {
+                    Some(id) if cooldown_ok && parts.len() <= 2 => {
+                        check_attack_reference(ctx, result, id, format!("{}[0]", path));
+                    }
+                    _ => result.add_error_with_path(
+                        "INVALID_SPECIAL_ATTACK",
+                        format!("Expected [attack_id, cooldown], found {}", attack),
+                        path,
+                    ),
+                }
+            }
+            Value::Object(obj) => {
+                if let Some(id) = obj.get("id").and_then(|v| v.as_str()) {
+                    check_attack_reference(ctx, result, id, format!("{}.id", path));
+                } else if !obj.get("type").is_some_and(|v| v.is_string()) {
+                    result.add_error_with_path(
+                        "INVALID_SPECIAL_ATTACK",
+                        "Special attack object must have an 'id' or a 'type'",
+                        path,
+                    );
+                }
+            }
+            _ => result.add_error_with_path(
+                "INVALID_SPECIAL_ATTACK",
+                format!("Unexpected special attack {}", attack),
+                path,
+            ),
+        }
+    }
 }
 
-fn validate_vehicle(value: &Value, result: &mut ValidationResult) {
+fn check_attack_reference(
+    ctx: &ValidationContext,
+    result: &mut ValidationResult,
+    id: &str,
+    path: String,
+) {
+    if !BUILTIN_ATTACKS.contains(&id) {
+        check_reference(ctx, result, &["monster_attack"], "monster attack", id, path);
+    }
+}
+
+/// Validate `upgrades`: `false` or an object with `half_life`/`age_grow` and `into`/`into_group`
+fn validate_upgrades(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
+    if value == &Value::Bool(false) {
+        return;
+    }
+    let Some(obj) = value.as_object() else {
+        result.add_error_with_path(
+            "INVALID_UPGRADES",
+            "'upgrades' must be an object or false",
+            "$.upgrades",
+        );
+        return;
+    };
+
+    for field in ["half_life", "age_grow"] {
+        if let Some(v) = obj.get(field) {
+            if v.as_i64().is_none_or(|n| n <= 0) {
+                result.add_error_with_path(
+                    "INVALID_UPGRADES",
+                    format!("'upgrades.{}' must be a positive integer (days)", field),
+                    format!("$.upgrades.{}", field),
+                );
+            }
+        }
+    }
+    if !obj.contains_key("half_life") && !obj.contains_key("age_grow") {
+        result.add_warning_with_path(
+            "INVALID_UPGRADES",
+            "'upgrades' should have a 'half_life' or 'age_grow'",
+            "$.upgrades",
+        );
+    }
+
+    let into = obj.get("into").and_then(|v| v.as_str());
+    let into_group = obj.get("into_group").and_then(|v| v.as_str());
+    if let Some(id) = into {
+        check_reference(ctx, result, &["MONSTER"], "monster", id, "$.upgrades.into");
+    }
+    if let Some(id) = into_group {
+        check_reference(
+            ctx,
+            result,
+            &["monstergroup"],
+            "monster group",
+            id,
+            "$.upgrades.into_group",
+        );
+    }
+    if into.is_none() && into_group.is_none() {
+        result.add_error_with_path(
+            "INVALID_UPGRADES",
+            "'upgrades' must have an 'into' monster or an 'into_group'",
+            "$.upgrades",
+        );
+    }
+}
+
+/// Validate a damage instance: a list of `{ "damage_type", "amount", ... }` objects
+fn validate_damage_instance(value: &Value, path: &str, result: &mut ValidationResult) {
+    let Some(units) = value.as_array() else {
+        result.add_error_with_path(
+            "INVALID_DAMAGE",
+            "Damage must be an array of damage units",
+            path,
+        );
+        return;
+    };
+
+    for (i, unit) in units.iter().enumerate() {
+        let unit_path = format!("{}[{}]", path, i);
+        let Some(obj) = unit.as_object() else {
+            result.add_error_with_path(
+                "INVALID_DAMAGE",
+                format!("Expected a damage unit object, found {}", unit),
+                unit_path,
+            );
+            continue;
+        };
+
+        match obj.get("damage_type").and_then(|v| v.as_str()) {
+            Some(damage_type) if DAMAGE_TYPES.contains(&damage_type) => {}
+            Some(damage_type) => result.add_error_with_path(
+                "INVALID_DAMAGE",
+                format!("Unknown damage type '{}'", damage_type),
+                format!("{}.damage_type", unit_path),
+            ),
+            None => result.add_error_with_path(
+                "INVALID_DAMAGE",
+                "Damage unit must have a 'damage_type'",
+                unit_path.clone(),
+            ),
+        }
+
+        for field in [
+            "amount",
+            "armor_penetration",
+            "armor_multiplier",
+            "damage_multiplier",
+        ] {
+            if obj.get(field).is_some_and(|v| !v.is_number()) {
+                result.add_error_with_path(
+                    "INVALID_DAMAGE",
+                    format!("'{}' must be a number", field),
+                    format!("{}.{}", unit_path, field),
+                );
+            }
+        }
+    }
+}
+
+/// Call `f` for a string or each string in an array, reporting anything else
+fn for_each_string(
+    value: &Value,
+    path: &str,
+    code: &str,
+    result: &mut ValidationResult,
+    mut f: impl FnMut(&str, String, &mut ValidationResult),
+) {
+    match value {
+        Value::String(s) => f(s, path.to_string(), result),
+        Value::Array(arr) => {
+            for (i, v) in arr.iter().enumerate() {
+                let item_path = format!("{}[{}]", path, i);
+                match v.as_str() {
+                    Some(s) => f(s, item_path, result),
+                    None => result.add_error_with_path(
+                        code,
+                        format!("Expected a string, found {}", v),
+                        item_path,
+                    ),
+                }
+            }
+        }
+        _ => result.add_error_with_path(
+            code,
+            format!("Expected a string or an array of strings, found {}", value),
+            path,
+        ),
+    }
+}
+
+fn validate_vehicle(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
     // Vehicles should have parts
     if value.get("parts").is_none() && value.get("copy-from").is_none() {
         result.add_warning("MISSING_PARTS", "Vehicle should have a 'parts' array");
     }
+
+    let mut mounts: BTreeMap<(i64, i64), Vec<MountedPart>> = BTreeMap::new();
+
+    if let Some(parts) = value.get("parts") {
+        let Some(parts) = parts.as_array() else {
+            result.add_error_with_path("INVALID_PARTS", "'parts' must be an array", "$.parts");
+            return;
+        };
+
+        for (i, entry) in parts.iter().enumerate() {
+            let path = format!("$.parts[{}]", i);
+            let Some(mount) = mount_point(entry) else {
+                result.add_error_with_path(
+                    "INVALID_PARTS",
+                    "Vehicle part entry must have integer 'x' and 'y'",
+                    path,
+                );
+                continue;
+            };
+
+            let mut ids = Vec::new();
+            if let Some(part) = entry.get("part") {
+                ids.push((part, format!("{}.part", path)));
+            }
+            if let Some(list) = entry.get("parts").and_then(|v| v.as_array()) {
+                for (j, part) in list.iter().enumerate() {
+                    // Either an ID or an object with "part" and extra settings
+                    let part = part.get("part").unwrap_or(part);
+                    ids.push((part, format!("{}.parts[{}]", path, j)));
+                }
+            }
+            if ids.is_empty() {
+                result.add_error_with_path(
+                    "INVALID_PARTS",
+                    "Vehicle part entry must have a 'part' or 'parts'",
+                    path,
+                );
+                continue;
+            }
+
+            for (id, id_path) in ids {
+                let Some(id) = id.as_str() else {
+                    result.add_error_with_path(
+                        "INVALID_PARTS",
+                        format!("Expected a vehicle part ID, found {}", id),
+                        id_path,
+                    );
+                    continue;
+                };
+                check_reference(
+                    ctx,
+                    result,
+                    &["vehicle_part"],
+                    "vehicle part",
+                    id,
+                    id_path.clone(),
+                );
+                mounts
+                    .entry(mount)
+                    .or_default()
+                    .push(MountedPart { id, path: id_path });
+            }
+        }
+    }
+
+    validate_vehicle_mounts(&mounts, ctx, result);
+
+    if let Some(items) = value.get("items") {
+        validate_vehicle_items(items, &mounts, ctx, result);
+    }
+}
+
+/// A part installed at a vehicle mount point
+struct MountedPart<'v> {
+    id: &'v str,
+    path: String,
+}
+
+/// Read the `x`/`y` coordinates of a vehicle part or item entry
+fn mount_point(entry: &Value) -> Option<(i64, i64)> {
+    Some((entry.get("x")?.as_i64()?, entry.get("y")?.as_i64()?))
+}
+
+/// Check that mounts form one connected structure and that every mount
+/// has a frame and no two parts competing for the same location
+fn validate_vehicle_mounts(
+    mounts: &BTreeMap<(i64, i64), Vec<MountedPart>>,
+    ctx: &ValidationContext,
+    result: &mut ValidationResult,
+) {
+    let Some(&start) = mounts.keys().next() else {
+        return;
+    };
+
+    // Flood fill from the first mount through orthogonal neighbours
+    let mut connected = HashSet::from([start]);
+    let mut queue = vec![start];
+    while let Some((x, y)) = queue.pop() {
+        for neighbour in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if mounts.contains_key(&neighbour) && connected.insert(neighbour) {
+                queue.push(neighbour);
+            }
+        }
+    }
+    for (&(x, y), parts) in mounts {
+        if !connected.contains(&(x, y)) {
+            result.add_warning_with_path(
+                "DISCONNECTED_PARTS",
+                format!(
+                    "Mount point ({}, {}) is not connected to the rest of the vehicle",
+                    x, y
+                ),
+                parts[0].path.clone(),
+            );
+        }
+    }
+
+    // Location checks need the part definitions
+    if !ctx.knows_any(&["vehicle_part"]) {
+        return;
+    }
+    for (&(x, y), parts) in mounts {
+        let mut locations: HashMap<&str, &MountedPart> = HashMap::new();
+        let mut has_frame = false;
+        let mut all_known = true;
+
+        for part in parts {
+            if !ctx.contains(&["vehicle_part"], part.id) {
+                all_known = false;
+                continue;
+            }
+            let Some(location) = ctx
+                .field(&["vehicle_part"], part.id, "location")
+                .and_then(|v| v.as_str())
+                .filter(|l| !l.is_empty())
+            else {
+                continue;
+            };
+            has_frame |= location == "structure";
+
+            if let Some(existing) = locations.insert(location, part) {
+                result.add_warning_with_path(
+                    "CONFLICTING_PARTS",
+                    format!(
+                        "'{}' and '{}' both occupy location '{}' at ({}, {})",
+                        existing.id, part.id, location, x, y
+                    ),
+                    part.path.clone(),
+                );
+            }
+        }
+
+        if !has_frame && all_known {
+            result.add_warning_with_path(
+                "MISSING_FRAME",
+                format!("Mount point ({}, {}) has no structural part (frame)", x, y),
+                parts[0].path.clone(),
+            );
+        }
+    }
 }
 
-fn validate_mapgen(value: &Value, result: &mut ValidationResult) {
+/// Validate `items`: item and item group spawns placed on the vehicle
+fn validate_vehicle_items(
+    value: &Value,
+    mounts: &BTreeMap<(i64, i64), Vec<MountedPart>>,
+    ctx: &ValidationContext,
+    result: &mut ValidationResult,
+) {
+    let Some(entries) = value.as_array() else {
+        result.add_error_with_path("INVALID_ITEMS", "'items' must be an array", "$.items");
+        return;
+    };
+
+    for (i, entry) in entries.iter().enumerate() {
+        let path = format!("$.items[{}]", i);
+        match mount_point(entry) {
+            None => result.add_error_with_path(
+                "INVALID_ITEMS",
+                "Vehicle item spawn must have integer 'x' and 'y'",
+                path.clone(),
+            ),
+            Some((x, y)) if !mounts.is_empty() && !mounts.contains_key(&(x, y)) => {
+                result.add_warning_with_path(
+                    "INVALID_ITEMS",
+                    format!(
+                        "Items spawn at ({}, {}) where the vehicle has no parts",
+                        x, y
+                    ),
+                    path.clone(),
+                );
+            }
+            Some(_) => {}
+        }
+
+        let mut found = false;
+        for (field, types, kind) in [
+            ("item", ITEM_TYPES, "item"),
+            ("items", ITEM_TYPES, "item"),
+            ("item_groups", &["item_group"][..], "item group"),
+        ] {
+            if let Some(ids) = entry.get(field) {
+                found = true;
+                let field_path = format!("{}.{}", path, field);
+                for_each_string(
+                    ids,
+                    &field_path,
+                    "INVALID_ITEMS",
+                    result,
+                    |id, id_path, result| {
+                        check_reference(ctx, result, types, kind, id, id_path);
+                    },
+                );
+            }
+        }
+        if !found {
+            result.add_error_with_path(
+                "INVALID_ITEMS",
+                "Vehicle item spawn must have 'items' or 'item_groups'",
+                path,
+            );
+        }
+    }
+}
+
+fn validate_mapgen(value: &Value, ctx: &ValidationContext, result: &mut ValidationResult) {
+    let is_nested =
+        value.get("nested_mapgen_id").is_some() || value.get("update_mapgen_id").is_some();
+
     // Mapgen should have om_terrain and object with rows
-    if value.get("om_terrain").is_none() {
+    if value.get("om_terrain").is_none() && !is_nested {
         result.add_warning(
             "MISSING_OM_TERRAIN",
             "Mapgen should have an 'om_terrain' field",
         );
     }
 
-    if let Some(obj) = value.get("object") {
-        if obj.get("rows").is_none() && obj.get("fill_ter").is_none() {
-            result.add_warning(
-                "MISSING_ROWS",
-                "Mapgen object should have 'rows' or 'fill_ter'",
+    let Some(obj) = value.get("object") else {
+        if value.get("copy-from").is_none() {
+            result.add_warning("MISSING_OBJECT", "Mapgen should have an 'object' field");
+        }
+        return;
+    };
+
+    if obj.get("rows").is_none() && obj.get("fill_ter").is_none() && !is_nested {
+        result.add_warning(
+            "MISSING_ROWS",
+            "Mapgen object should have 'rows' or 'fill_ter'",
+        );
+    }
+
+    // A mapgen the typed model can't read has its problems reported
+    // elsewhere; its placements just aren't checked against the map size
+    let size = Mapgen::from_json(value)
+        .ok()
+        .and_then(|mapgen| mapgen.size());
+    let rows = obj.get("rows").map(|rows| mapgen_rows(rows, result));
+
+    if let (Some(rows), Some((width, height))) = (&rows, size) {
+        if rows.len() != height {
+            result.add_warning_with_path(
+                "INVALID_ROWS",
+                format!(
+                    "Expected {} rows for this mapgen, found {}",
+                    height,
+                    rows.len()
+                ),
+                "$.object.rows",
             );
         }
-    } else if value.get("copy-from").is_none() {
-        result.add_warning("MISSING_OBJECT", "Mapgen should have an 'object' field");
+        for (i, row) in rows.iter().enumerate() {
+            let row_width = row.chars().count();
+            if row_width != width {
+                result.add_warning_with_path(
+                    "INVALID_ROWS",
+                    format!(
+                        "Row {} is {} characters wide, expected {}",
+                        i, row_width, width
+                    ),
+                    format!("$.object.rows[{}]", i),
+                );
+            }
+        }
+    }
+
+    if let Some(fill_ter) = obj.get("fill_ter").and_then(|v| v.as_str()) {
+        check_reference(
+            ctx,
+            result,
+            &["terrain"],
+            "terrain",
+            fill_ter,
+            "$.object.fill_ter",
+        );
+    }
+    let parameters = obj
+        .get("parameters")
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+    // Sections whose entries are plain IDs (terrain, furniture, traps)
+    for section in SYMBOL_SECTIONS {
+        let Some(namespace) = section.namespace else {
+            continue;
+        };
+        if let Some(mappings) = obj.get(section.name).and_then(|v| v.as_object()) {
+            for (symbol, mapping) in mappings {
+                let path = child_path(&format!("$.object.{}", section.name), symbol);
+                for candidate in palette::mapping_candidates(mapping, section, &parameters) {
+                    if let Some(id) = &candidate.id {
+                        check_reference(ctx, result, &[namespace], namespace, id, path.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(rows) = &rows {
+        if let Some(defined) = defined_symbols(obj, ctx, result) {
+            validate_row_symbols(rows, &defined, obj.get("fill_ter").is_some(), result);
+        }
+    }
+
+    if let Some((width, height)) = size {
+        validate_placement_bounds(obj, width as i64, height as i64, result);
+    }
+}
+
+/// Read `rows` as strings, reporting non-string entries
+fn mapgen_rows<'v>(value: &'v Value, result: &mut ValidationResult) -> Vec<&'v str> {
+    let Some(rows) = value.as_array() else {
+        result.add_warning_with_path(
+            "INVALID_ROWS",
+            "'rows' must be an array of strings",
+            "$.object.rows",
+        );
+        return Vec::new();
+    };
+
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            row.as_str().unwrap_or_else(|| {
+                result.add_warning_with_path(
+                    "INVALID_ROWS",
+                    format!("Row {} must be a string", i),
+                    format!("$.object.rows[{}]", i),
+                );
+                ""
+            })
+        })
+        .collect()
+}
+
+/// Collect every symbol defined inline or by included palettes.
+/// Returns None if a palette can't be resolved, since coverage is then unknown.
+fn defined_symbols(
+    obj: &Value,
+    ctx: &ValidationContext,
+    result: &mut ValidationResult,
+) -> Option<HashSet<String>> {
+    let mut defined = HashSet::new();
+    let mut complete = true;
+    collect_symbols(obj, &mut defined);
+
+    if let Some(palettes) = obj.get("palettes").and_then(|v| v.as_array()) {
+        for (i, palette) in palettes.iter().enumerate() {
+            match palette.as_str() {
+                Some(id) => {
+                    check_reference(
+                        ctx,
+                        result,
+                        &["palette"],
+                        "palette",
+                        id,
+                        format!("$.object.palettes[{}]", i),
+                    );
+                    let mut visited = HashSet::new();
+                    complete &= collect_palette_symbols(id, ctx, &mut defined, &mut visited);
+                }
+                // Parameterized palette choice; depends on the roll
+                None => complete = false,
+            }
+        }
+    }
+
+    complete.then_some(defined)
+}
+
+/// Add the symbols of a palette and the palettes it includes; false if any are missing
+fn collect_palette_symbols(
+    id: &str,
+    ctx: &ValidationContext,
+    defined: &mut HashSet<String>,
+    visited: &mut HashSet<String>,
+) -> bool {
+    if !visited.insert(id.to_string()) {
+        return true;
+    }
+    let Some(palette) = ctx.get(&["palette"], id) else {
+        return false;
+    };
+
+    collect_symbols(palette, defined);
+    let mut complete = true;
+    for nested in palette
+        .get("palettes")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        complete &= match nested.as_str() {
+            Some(nested_id) => collect_palette_symbols(nested_id, ctx, defined, visited),
+            None => false,
+        };
+    }
+    complete
+}
+
+fn collect_symbols(obj: &Value, defined: &mut HashSet<String>) {
+    for section in SYMBOL_SECTIONS {
+        if let Some(mappings) = obj.get(section.name).and_then(|v| v.as_object()) {
+            defined.extend(mappings.keys().cloned());
+        }
+    }
+}
+
+/// Report each row character that no mapping defines
+fn validate_row_symbols(
+    rows: &[&str],
+    defined: &HashSet<String>,
+    has_fill_ter: bool,
+    result: &mut ValidationResult,
+) {
+    // Without a mapping, fill_ter still provides the terrain for a symbol
+    if has_fill_ter {
+        return;
+    }
+
+    for (row_index, row) in rows.iter().enumerate() {
+        let mut undefined: BTreeMap<char, Vec<usize>> = BTreeMap::new();
+        for (column, symbol) in row.chars().enumerate() {
+            if !defined.contains(symbol.encode_utf8(&mut [0; 4]) as &str) {
+                undefined.entry(symbol).or_default().push(column);
+            }
+        }
+
+        for (symbol, columns) in undefined {
+            let plural = if columns.len() > 1 { "s" } else { "" };
+            let columns: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+            result.add_warning_with_path(
+                "UNDEFINED_SYMBOL",
+                format!(
+                    "Symbol '{}' is not defined (row {}, column{} {})",
+                    symbol,
+                    row_index,
+                    plural,
+                    columns.join(", ")
+                ),
+                format!("$.object.rows[{}]", row_index),
+            );
+        }
+    }
+}
+
+/// Check the `x`/`y` coordinates of every `place_*` entry are inside the map
+fn validate_placement_bounds(obj: &Value, width: i64, height: i64, result: &mut ValidationResult) {
+    let Some(obj) = obj.as_object() else {
+        return;
+    };
+
+    for (section, entries) in obj.iter().filter(|(k, _)| k.starts_with("place_")) {
+        let Some(entries) = entries.as_array() else {
+            continue;
+        };
+        for (i, entry) in entries.iter().enumerate() {
+            for (axis, limit) in [("x", width), ("y", height)] {
+                let Some(coordinate) = entry.get(axis) else {
+                    continue;
+                };
+                // A fixed coordinate or a [min, max] range
+                let values: Vec<i64> = match coordinate {
+                    Value::Array(range) => range.iter().filter_map(|v| v.as_i64()).collect(),
+                    other => other.as_i64().into_iter().collect(),
+                };
+                if let Some(bad) = values.iter().find(|v| !(0..limit).contains(*v)) {
+                    result.add_warning_with_path(
+                        "OUT_OF_BOUNDS",
+                        format!(
+                            "{} {} = {} is outside the {}x{} map",
+                            section, axis, bad, width, height
+                        ),
+                        format!("$.object.{}[{}].{}", section, i, axis),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Fill in line numbers for errors and warnings that have a JSON path
+fn annotate_lines(result: &mut ValidationResult, text: &str) {
+    let lines = value_lines(text);
+    let find = |path: &str| {
+        // Fall back to the closest enclosing value if the exact path isn't present
+        let mut path = path;
+        loop {
+            if let Some(line) = lines.get(path) {
+                return Some(*line);
+            }
+            path = &path[..path.rfind(['.', '['])?];
+        }
+    };
+
+    for error in &mut result.errors {
+        if error.line.is_none() {
+            error.line = error.path.as_deref().and_then(find);
+        }
+    }
+    for warning in &mut result.warnings {
+        if warning.line.is_none() {
+            warning.line = warning.path.as_deref().and_then(find);
+        }
+    }
+}
+
+/// Map the JSON path of every value in `text` (e.g. "$.components[0][1]")
+/// to the 1-based line where it starts
+fn value_lines(text: &str) -> HashMap<String, usize> {
+    let mut scanner = LineScanner {
+        bytes: text.as_bytes(),
+        pos: 0,
+        line: 1,
+        lines: HashMap::new(),
+    };
+    scanner.value("$".to_string());
+    scanner.lines
+}
+
+struct LineScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+    lines: HashMap<String, usize>,
+}
+
+impl LineScanner<'_> {
+    /// Scan one value; returns None on malformed input
+    fn value(&mut self, path: String) -> Option<()> {
+        self.skip_whitespace();
+        self.lines.insert(path.clone(), self.line);
+
+        match *self.bytes.get(self.pos)? {
+            b'{' => {
+                self.pos += 1;
+                loop {
+                    self.skip_whitespace();
+                    match *self.bytes.get(self.pos)? {
+                        b'}' => {
+                            self.pos += 1;
+                            return Some(());
+                        }
+                        b',' => self.pos += 1,
+                        b'"' => {
+                            let key = self.string()?;
+                            self.skip_whitespace();
+                            if *self.bytes.get(self.pos)? != b':' {
+                                return None;
+                            }
+                            self.pos += 1;
+                            self.value(child_path(&path, &key))?;
+                        }
+                        _ => return None,
+                    }
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut index = 0;
+                loop {
+                    self.skip_whitespace();
+                    match *self.bytes.get(self.pos)? {
+                        b']' => {
+                            self.pos += 1;
+                            return Some(());
+                        }
+                        b',' => self.pos += 1,
+                        _ => {
+                            self.value(format!("{}[{}]", path, index))?;
+                            index += 1;
+                        }
+                    }
+                }
+            }
+            b'"' => self.string().map(|_| ()),
+            _ => {
+                // Number, true, false or null
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|b| !matches!(b, b',' | b']' | b'}') && !b.is_ascii_whitespace())
+                {
+                    self.pos += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    /// Scan a string starting at the opening quote and return its contents
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match *self.bytes.get(self.pos)? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => self.pos += 1,
+            }
+        }
+        let raw = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+        serde_json::from_str(raw).ok()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.bytes.get(self.pos) {
+            if !b.is_ascii_whitespace() {
+                break;
+            }
+            if *b == b'\n' {
+                self.line += 1;
+            }
+            self.pos += 1;
+        }
     }
 }
 
@@ -42,6 +42,9 @@ export function ValidationDisplay({ validation }: ValidationDisplayProps) {
               {warning.path && (
                 <span className="text-zinc-500 font-mono">{warning.path}</span>
               )}
+              {warning.line && (
+                <span className="text-zinc-500">line {warning.line}</span>
+              )}
             </div>
           ))}
         </div>
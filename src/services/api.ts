@@ -7,9 +7,33 @@ import type {
   WorkspaceState,
   EntityData,
   UpdateResult,
-  SearchResult,
+  SearchPage,
+  FullTextPage,
   SaveResult,
+  ReorganizeResult,
   AvailableModInfo,
+  FlagInfo,
+  MigrateResult,
+  Fix,
+  ApplyFixesResult,
+  IdIssue,
+  ReplaceQuery,
+  EntityChanges,
+  AcceptedChanges,
+  ReplaceResult,
+  RenameResult,
+  CopyMode,
+  CopyResult,
+  MinimizeResult,
+  HistoryState,
+  HistoryStep,
+  DiffBase,
+  EntityDiff,
+  VersionReport,
+  PackGitStatus,
+  EntityBlame,
+  MapgenData,
+  MapgenEdit,
 } from "../types";
 
 // Workspace commands
@@ -67,9 +91,33 @@ export async function updateEntity(
 export async function searchEntities(
   query: string,
   entityTypes?: string[],
-  packIds?: PackId[]
-): Promise<SearchResult[]> {
-  return invoke("search_entities", { query, entityTypes, packIds });
+  packIds?: PackId[],
+  offset?: number,
+  limit?: number
+): Promise<SearchPage> {
+  return invoke("search_entities", {
+    query,
+    entityTypes,
+    packIds,
+    offset,
+    limit,
+  });
+}
+
+export async function fullTextSearch(
+  query: string,
+  entityTypes?: string[],
+  packIds?: PackId[],
+  offset?: number,
+  limit?: number
+): Promise<FullTextPage> {
+  return invoke("full_text_search", {
+    query,
+    entityTypes,
+    packIds,
+    offset,
+    limit,
+  });
 }
 
 // File commands
@@ -78,6 +126,228 @@ export async function savePack(packId: PackId): Promise<SaveResult> {
   return invoke("save_pack", { packId });
 }
 
+export async function moveEntities(
+  packId: PackId,
+  entityKeys: EntityKey[],
+  targetFile: string
+): Promise<ReorganizeResult> {
+  return invoke("move_entities", { packId, entityKeys, targetFile });
+}
+
+export async function splitFileByType(
+  packId: PackId,
+  file: string
+): Promise<ReorganizeResult> {
+  return invoke("split_file_by_type", { packId, file });
+}
+
+export async function mergeFiles(
+  packId: PackId,
+  files: string[],
+  targetFile: string
+): Promise<ReorganizeResult> {
+  return invoke("merge_files", { packId, files, targetFile });
+}
+
+// Unit commands
+
+export async function normalizeUnits(
+  packId: PackId,
+  entityKeys?: EntityKey[]
+): Promise<MigrateResult> {
+  return invoke("normalize_units", { packId, entityKeys });
+}
+
+// Flag commands
+
+export async function listKnownFlags(entityType?: string): Promise<FlagInfo[]> {
+  return invoke("list_known_flags", { entityType });
+}
+
+// Migration commands
+
+export async function migrateDeprecatedFields(
+  packId: PackId,
+  entityKeys?: EntityKey[]
+): Promise<MigrateResult> {
+  return invoke("migrate_deprecated_fields", { packId, entityKeys });
+}
+
+// Fix commands
+
+export async function applyFix(
+  packId: PackId,
+  entityKey: EntityKey,
+  fix: Fix
+): Promise<UpdateResult> {
+  return invoke("apply_fix", { packId, entityKey, fix });
+}
+
+export async function applyFixesByCode(
+  packId: PackId,
+  code: string
+): Promise<ApplyFixesResult> {
+  return invoke("apply_fixes_by_code", { packId, code });
+}
+
+// ID commands
+
+export async function checkPackIds(
+  packId: PackId,
+  prefixPattern?: string | null
+): Promise<IdIssue[]> {
+  return invoke("check_pack_ids", { packId, prefixPattern });
+}
+
+// Find and replace commands
+
+export async function previewReplace(
+  query: ReplaceQuery,
+  packIds?: PackId[],
+  entityTypes?: string[]
+): Promise<EntityChanges[]> {
+  return invoke("preview_replace", { query, packIds, entityTypes });
+}
+
+export async function applyReplace(
+  query: ReplaceQuery,
+  accepted: AcceptedChanges[]
+): Promise<ReplaceResult> {
+  return invoke("apply_replace", { query, accepted });
+}
+
+// Rename commands
+
+export async function renameEntity(
+  packId: PackId,
+  entityKey: EntityKey,
+  newId: string,
+  addMigration: boolean
+): Promise<RenameResult> {
+  return invoke("rename_entity", { packId, entityKey, newId, addMigration });
+}
+
+// Copy commands
+
+export async function copyEntityToPack(
+  sourcePackId: PackId,
+  entityKey: EntityKey,
+  targetPackId: PackId,
+  mode: CopyMode,
+  newId?: string,
+  prune: boolean = false,
+  targetFile?: string
+): Promise<CopyResult> {
+  return invoke("copy_entity_to_pack", {
+    sourcePackId,
+    entityKey,
+    targetPackId,
+    mode,
+    newId,
+    prune,
+    targetFile,
+  });
+}
+
+export async function minimizeOverride(
+  packId: PackId,
+  entityKey: EntityKey,
+  apply: boolean
+): Promise<MinimizeResult> {
+  return invoke("minimize_override", { packId, entityKey, apply });
+}
+
+// History commands
+
+export async function undo(packId: PackId): Promise<HistoryStep | null> {
+  return invoke("undo", { packId });
+}
+
+export async function redo(packId: PackId): Promise<HistoryStep | null> {
+  return invoke("redo", { packId });
+}
+
+export async function getHistoryState(packId: PackId): Promise<HistoryState> {
+  return invoke("get_history_state", { packId });
+}
+
+// Diff commands
+
+export async function diffEntity(
+  packId: PackId,
+  entityKey: EntityKey,
+  base: DiffBase
+): Promise<EntityDiff> {
+  return invoke("diff_entity", { packId, entityKey, base });
+}
+
+export async function revertEntityField(
+  packId: PackId,
+  entityKey: EntityKey,
+  base: DiffBase,
+  path: string
+): Promise<UpdateResult> {
+  return invoke("revert_entity_field", { packId, entityKey, base, path });
+}
+
+export async function comparePackVersions(
+  oldPath: string,
+  newPath: string,
+  excludeDirs?: string[],
+  isBaseGame?: boolean
+): Promise<VersionReport> {
+  return invoke("compare_pack_versions", {
+    oldPath,
+    newPath,
+    excludeDirs,
+    isBaseGame,
+  });
+}
+
+// Git commands
+
+export async function getPackGitStatus(packId: PackId): Promise<PackGitStatus> {
+  return invoke("get_pack_git_status", { packId });
+}
+
+export async function getCommittedEntity(
+  packId: PackId,
+  entityKey: EntityKey
+): Promise<string | null> {
+  return invoke("get_committed_entity", { packId, entityKey });
+}
+
+export async function blameEntity(
+  packId: PackId,
+  entityKey: EntityKey
+): Promise<EntityBlame> {
+  return invoke("blame_entity", { packId, entityKey });
+}
+
+export async function revertEntityToHead(
+  packId: PackId,
+  entityKey: EntityKey
+): Promise<UpdateResult> {
+  return invoke("revert_entity_to_head", { packId, entityKey });
+}
+
+// Mapgen commands
+
+export async function getMapgen(
+  packId: PackId,
+  entityKey: EntityKey
+): Promise<MapgenData> {
+  return invoke("get_mapgen", { packId, entityKey });
+}
+
+export async function updateMapgen(
+  packId: PackId,
+  entityKey: EntityKey,
+  edit: MapgenEdit
+): Promise<UpdateResult> {
+  return invoke("update_mapgen", { packId, entityKey, edit });
+}
+
 // Dialog helpers
 
 export async function openPackDialog(): Promise<string | null> {
@@ -5,6 +5,7 @@ export interface AppSettings {
   gamePath: string | null;
   tileset: string | null;
   modDirectories: string[];
+  idPrefixPattern?: string | null;
 }
 
 export interface GamePathInfo {
@@ -106,12 +106,214 @@ export interface ValidationError {
   message: string;
   path: string | null;
   line: number | null;
+  fix: Fix | null;
 }
 
 export interface ValidationWarning {
   code: string;
   message: string;
   path: string | null;
+  line: number | null;
+  fix: Fix | null;
+}
+
+export interface Fix {
+  description: string;
+  operations: PatchOperation[];
+}
+
+export type PatchOperation =
+  | { op: "add"; path: string; value: unknown }
+  | { op: "remove"; path: string }
+  | { op: "replace"; path: string; value: unknown }
+  | { op: "move"; from: string; path: string };
+
+export interface ApplyFixesResult {
+  entitiesChanged: EntityKey[];
+  fixesApplied: number;
+  rejected: EntityKey[];
+}
+
+export interface IdIssue {
+  code: "DUPLICATE_ID" | "UNINTENTIONAL_OVERRIDE" | "ID_COLLISION" | "ID_PREFIX";
+  message: string;
+  entityKey: EntityKey;
+  entityId: string;
+  entityType: string;
+  otherPackId: PackId | null;
+  otherPackName: string | null;
+}
+
+// Find and replace types
+export interface ReplaceQuery {
+  /** Text to find, or a regex when `regex` is set */
+  find: string;
+  /** Replacement; with `regex`, `$1` / `${name}` insert capture groups */
+  replace: string;
+  regex?: boolean;
+  caseSensitive?: boolean;
+  /** JSONPath limiting where to look; the whole entity if unset */
+  path?: string | null;
+}
+
+export interface ProposedChange {
+  path: string;
+  before: string;
+  after: string;
+}
+
+export interface EntityChanges {
+  packId: PackId;
+  packName: string;
+  entityKey: EntityKey;
+  entityId: string;
+  entityType: string;
+  changes: ProposedChange[];
+}
+
+export interface AcceptedChanges {
+  packId: PackId;
+  entityKey: EntityKey;
+  paths: string[];
+}
+
+export interface ReplaceResult {
+  entitiesChanged: EntityKey[];
+  valuesReplaced: number;
+  rejected: EntityKey[];
+}
+
+// Rename types
+export interface ReferenceSite {
+  packId: PackId;
+  packName: string;
+  entityKey: EntityKey;
+  path: string;
+}
+
+export interface RenameResult {
+  newKey: EntityKey;
+  /** References rewritten to the new ID */
+  updated: ReferenceSite[];
+  /** References in read-only packs that still use the old ID */
+  notUpdated: ReferenceSite[];
+  /** Key of the MIGRATION entry added for the old ID, if requested */
+  migrationKey: EntityKey | null;
+}
+
+// Copy types
+/** "copy": independent copy with a new ID; "copyFrom": copy-from stub with a
+ * new ID; "override": same ID, replacing the original */
+export type CopyMode = "copy" | "copyFrom" | "override";
+
+export interface CopyResult {
+  entityKey: EntityKey;
+  /** File the entity was added to, relative to the target pack */
+  sourceFile: string;
+}
+
+export interface MinimizeResult {
+  /** The minimized entity */
+  jsonText: string;
+  /** Top-level fields that are now inherited instead of restated */
+  inherited: string[];
+  /** The update, when applied */
+  update: UpdateResult | null;
+}
+
+// History types
+export interface HistoryState {
+  undoLabel: string | null;
+  redoLabel: string | null;
+}
+
+export interface HistoryStep {
+  label: string;
+  /** Keys whose entity changed, appeared or disappeared */
+  changedKeys: EntityKey[];
+}
+
+// Diff types
+export type DiffBase = "disk" | "base" | "head";
+
+export interface DiffEntry {
+  /** JSON path of the value (e.g. "$.melee_damage.bash") */
+  path: string;
+  kind: "added" | "removed" | "changed";
+  before: unknown | null;
+  after: unknown | null;
+}
+
+export interface EntityDiff {
+  base: DiffBase;
+  /** Pack the base definition comes from (for "base") */
+  basePackName: string | null;
+  /** Whether there was anything to compare against */
+  hasBase: boolean;
+  entries: DiffEntry[];
+}
+
+export interface ChangedEntity {
+  id: string;
+  entries: DiffEntry[];
+}
+
+export interface TypeChanges {
+  entityType: string;
+  /** IDs only in the new version */
+  added: string[];
+  /** IDs only in the old version */
+  removed: string[];
+  changed: ChangedEntity[];
+}
+
+export interface BrokenReference {
+  /** The removed ID */
+  id: string;
+  packId: PackId;
+  packName: string;
+  entityKey: EntityKey;
+  path: string;
+}
+
+export interface VersionReport {
+  oldName: string;
+  newName: string;
+  types: TypeChanges[];
+  brokenReferences: BrokenReference[];
+}
+
+// Git types
+export interface FileStatus {
+  /** Path relative to the pack root */
+  path: string;
+  staged: boolean;
+  modified: boolean;
+  untracked: boolean;
+  /** Has edits in Catalyst that haven't been saved */
+  unsaved: boolean;
+}
+
+export interface PackGitStatus {
+  isRepository: boolean;
+  /** null when HEAD is detached or outside a repository */
+  branch: string | null;
+  files: FileStatus[];
+}
+
+export interface CommitInfo {
+  hash: string;
+  author: string;
+  /** ISO 8601 author date */
+  date: string;
+  summary: string;
+}
+
+export interface EntityBlame {
+  /** null if never committed or last changed before the commits searched */
+  lastCommit: CommitInfo | null;
+  /** Whether the entity differs from the last commit */
+  uncommitted: boolean;
 }
 
 // Search types
@@ -122,14 +324,82 @@ export interface SearchResult {
   entityId: string;
   entityType: string;
   displayName: string | null;
+  /** JSON paths (in the effective JSON) that matched the query */
+  matchedPaths: string[];
+  /** Relevance; higher is better */
+  score: number;
+}
+
+export interface SearchPage {
+  results: SearchResult[];
+  /** Number of matches across all pages */
+  total: number;
+  offset: number;
+}
+
+export interface SnippetPart {
+  text: string;
+  highlight: boolean;
+}
+
+export interface Snippet {
+  /** JSON path of the string value */
+  path: string;
+  parts: SnippetPart[];
+}
+
+export interface FullTextResult {
+  packId: PackId;
+  packName: string;
+  entityKey: EntityKey;
+  entityId: string;
+  entityType: string;
+  displayName: string | null;
+  /** Relevance; higher is better */
+  score: number;
+  snippets: Snippet[];
+}
+
+export interface FullTextPage {
+  results: FullTextResult[];
+  /** Number of matches across all pages */
+  total: number;
+  offset: number;
 }
 
 // Save types
 export interface SaveResult {
   filesWritten: string[];
+  /** Files removed because every entity was moved out of them */
+  filesDeleted: string[];
   entitiesSaved: number;
 }
 
+export interface ReorganizeResult {
+  /** Keys of the entities that moved */
+  moved: EntityKey[];
+  /** The pack's entity tree afterwards */
+  entityTree: EntityTree;
+}
+
+// Flag and migration types
+export type FlagCategory = "item" | "terrain" | "monster" | "vehicle_part";
+
+export interface FlagInfo {
+  id: string;
+  source: "engine" | "json";
+  category: FlagCategory;
+  info: string | null;
+}
+
+export interface MigrateResult {
+  entitiesChanged: EntityKey[];
+  /** Fields migrated, or values converted when normalizing units */
+  fieldsMigrated: number;
+  /** Entities whose migrated JSON failed validation and was not stored */
+  rejected: EntityKey[];
+}
+
 // UI state types
 export interface Selection {
   packId: PackId | null;
@@ -181,16 +451,34 @@ export interface FurnitureInfo {
 }
 
 // Palette types
+export interface SymbolCandidate {
+  id: string | null;
+  weight: number;
+  parameter: string | null;
+}
+
+/** A symbol's entry in one section (terrain, items, nested...) of one palette */
+export interface SymbolDefinition {
+  section: string;
+  palette: string;
+  candidates: SymbolCandidate[];
+  value: unknown;
+}
+
 export interface SymbolMapping {
   symbol: string;
   terrain: string | null;
   furniture: string | null;
+  /** In the order they are applied; the last terrain and furniture win */
+  definitions: SymbolDefinition[];
 }
 
 export interface PaletteData {
   id: string;
   mappings: SymbolMapping[];
+  /** Every palette applied, nested ones included */
   includes: string[];
+  missing: string[];
 }
 
 export type PaletteSourceType = "external" | "inline";
@@ -207,6 +495,51 @@ export interface ResolvedSymbol {
   source: PaletteSource;
 }
 
+// Mapgen types
+export type MapgenKind = "terrain" | "nested" | "update";
+
+/** A palette by ID, or a choice of palettes made at generation time */
+export type PaletteRef = string | Record<string, unknown>;
+
+/** An entry of a place_* section; x and y are a position or a range */
+export interface Placement {
+  x: number | number[];
+  y: number | number[];
+  [field: string]: unknown;
+}
+
+export interface MapgenData {
+  kind: MapgenKind;
+  omTerrain: string[];
+  width: number | null;
+  height: number | null;
+  weight: number | null;
+  fillTer: string | null;
+  rows: string[];
+  palettes: PaletteRef[];
+  terrain: Record<string, unknown>;
+  furniture: Record<string, unknown>;
+  items: Record<string, unknown>;
+  monsters: Record<string, unknown>;
+  nested: Record<string, unknown>;
+  placements: Record<string, Placement[]>;
+}
+
+/** Fields left out are unchanged; an empty fillTer removes it */
+export interface MapgenEdit {
+  rows?: string[];
+  fillTer?: string;
+  weight?: number;
+  palettes?: PaletteRef[];
+  terrain?: Record<string, unknown>;
+  furniture?: Record<string, unknown>;
+  items?: Record<string, unknown>;
+  monsters?: Record<string, unknown>;
+  nested?: Record<string, unknown>;
+  /** place_* sections to replace by name; an empty list removes one */
+  placements?: Record<string, Placement[]>;
+}
+
 // Map editor types
 export type MapTool = "hand" | "paint" | "line" | "box" | "fill" | "eyedropper";
 